@@ -0,0 +1,486 @@
+use std::{f32::consts::FRAC_PI_2, fs, path::Path, time::Duration};
+
+use bevy::{
+    asset::io::file::FileAssetReader, color::palettes, prelude::*,
+    time::common_conditions::on_timer, window::PrimaryWindow,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    blueprint_ui::BlueprintUiPlugin,
+    build::{
+        cursor_ground_position, place_buildings, BuildEvent, PlacementChecker, PlacementError,
+        CELL_SIZE, GROUND_LEVEL,
+    },
+    building::{Building, BuildingAssets, BuildingKind},
+    building_grid::{rotated_footprint, world_to_cell},
+    inventory::Inventory,
+    player::{Player, PlayerState},
+    player_input::{InputParam, PlayerAction},
+};
+
+const BLUEPRINT_DIRECTORY: &str = "assets/blueprints";
+const GHOST_COLOR: Color = Color::srgba(0.4, 0.6, 1.0, 0.25);
+const SELECTION_COLOR: Srgba = palettes::css::DEEP_SKY_BLUE;
+const FILL_INTERVAL: f32 = 0.5;
+
+pub struct BlueprintPlugin;
+
+impl Plugin for BlueprintPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(BlueprintUiPlugin)
+            .init_resource::<BlueprintLibrary>()
+            .init_resource::<BlueprintTool>()
+            .add_event::<SaveBlueprintEvent>()
+            .add_event::<StampBlueprintEvent>()
+            .add_systems(Startup, setup)
+            .add_systems(OnExit(PlayerState::Blueprint), reset_tool)
+            .add_systems(
+                Update,
+                (
+                    start_blueprint_mode.run_if(in_state(PlayerState::Normal)),
+                    (
+                        stop_blueprint_mode,
+                        select_region,
+                        stamp_on_click,
+                        draw_blueprint_tool,
+                    )
+                        .chain()
+                        .run_if(in_state(PlayerState::Blueprint)),
+                    save_blueprints.run_if(on_event::<SaveBlueprintEvent>()),
+                    stamp_blueprints.run_if(on_event::<StampBlueprintEvent>()),
+                    // Ghosts wait while menus are open or buildings are being torn down
+                    fill_ghosts
+                        .run_if(
+                            in_state(PlayerState::Normal)
+                                .or_else(in_state(PlayerState::BuildingMode))
+                                .or_else(in_state(PlayerState::Blueprint)),
+                        )
+                        .run_if(on_timer(Duration::from_secs_f32(FILL_INTERVAL))),
+                ),
+            );
+    }
+}
+
+// Piece of a blueprint, placed relative to the blueprint's lowest cell
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BlueprintPiece {
+    pub cell: (i32, i32),
+    pub kind: BuildingKind,
+    pub quarter_turns: u32,
+}
+
+impl BlueprintPiece {
+    // Transform of the piece when the blueprint starts at `origin`
+    fn transform(&self, origin: IVec2) -> Transform {
+        let rotation = Quat::from_rotation_y(self.quarter_turns as f32 * FRAC_PI_2);
+        let footprint = rotated_footprint(self.kind.footprint(), rotation).as_vec2() * CELL_SIZE;
+        let corner = (origin + IVec2::from(self.cell)).as_vec2() * CELL_SIZE;
+
+        Transform::from_xyz(
+            corner.x + footprint.x / 2.0,
+            GROUND_LEVEL + self.kind.size().y / 2.0,
+            corner.y + footprint.y / 2.0,
+        )
+        .with_rotation(rotation)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Blueprint {
+    pub name: String,
+    pub pieces: Vec<BlueprintPiece>,
+}
+
+impl Blueprint {
+    // Names differing only in case or punctuation share a file, and so count
+    // as the same blueprint
+    fn file_name(&self) -> String {
+        let stem: String = self
+            .name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        format!("{}.ron", stem)
+    }
+}
+
+#[derive(Resource)]
+pub struct BlueprintLibrary {
+    blueprints: Vec<Blueprint>,
+}
+
+impl BlueprintLibrary {
+    pub fn get(&self, blueprint: usize) -> Option<&Blueprint> {
+        self.blueprints.get(blueprint)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Blueprint)> {
+        self.blueprints.iter().enumerate()
+    }
+
+    fn directory() -> std::path::PathBuf {
+        FileAssetReader::get_base_path().join(BLUEPRINT_DIRECTORY)
+    }
+
+    // Every `.ron` file in the directory holds a single blueprint
+    fn load_directory(&mut self, directory: &Path) {
+        let Ok(entries) = fs::read_dir(directory) else {
+            return;
+        };
+
+        let mut paths: Vec<_> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "ron"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let blueprint = fs::read_to_string(&path)
+                .map_err(|error| error.to_string())
+                .and_then(|data| {
+                    ron::from_str::<Blueprint>(&data).map_err(|error| error.to_string())
+                });
+
+            match blueprint {
+                Ok(blueprint) => self.blueprints.push(blueprint),
+                Err(error) => error!("Could not load blueprint {}: {}", path.display(), error),
+            }
+        }
+    }
+
+    // Saving under a name that maps to an existing file replaces that blueprint
+    fn save(&mut self, blueprint: Blueprint) -> Result<(), String> {
+        let file_name = blueprint.file_name();
+        let directory = Self::directory();
+
+        let data = ron::ser::to_string_pretty(&blueprint, ron::ser::PrettyConfig::default())
+            .map_err(|error| error.to_string())?;
+        fs::create_dir_all(&directory).map_err(|error| error.to_string())?;
+        fs::write(directory.join(&file_name), data).map_err(|error| error.to_string())?;
+
+        match self
+            .blueprints
+            .iter_mut()
+            .find(|existing| existing.file_name() == file_name)
+        {
+            Some(existing) => *existing = blueprint,
+            None => self.blueprints.push(blueprint),
+        }
+
+        Ok(())
+    }
+}
+
+impl FromWorld for BlueprintLibrary {
+    fn from_world(_world: &mut World) -> Self {
+        let mut library = BlueprintLibrary {
+            blueprints: Vec::new(),
+        };
+        library.load_directory(&Self::directory());
+        library
+    }
+}
+
+// Selection and stamping state while in `PlayerState::Blueprint`
+#[derive(Resource, Default)]
+pub struct BlueprintTool {
+    selection_start: Option<IVec2>,
+    pub selection: Option<(IVec2, IVec2)>,
+    pub name: String,
+    pub stamping: Option<usize>,
+}
+
+// Region bounds are inclusive cell coordinates
+#[derive(Event)]
+pub struct SaveBlueprintEvent {
+    pub name: String,
+    pub min: IVec2,
+    pub max: IVec2,
+}
+
+#[derive(Event)]
+pub struct StampBlueprintEvent {
+    pub blueprint: usize,
+    pub origin: IVec2,
+}
+
+// Planned building waiting for the resources to be built
+#[derive(Component)]
+pub struct BlueprintGhost {
+    pub kind: BuildingKind,
+}
+
+#[derive(Resource)]
+struct GhostMaterial(Handle<StandardMaterial>);
+
+fn setup(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
+    commands.insert_resource(GhostMaterial(
+        materials.add(StandardMaterial::from_color(GHOST_COLOR)),
+    ));
+}
+
+fn start_blueprint_mode(input: InputParam, mut next_player_state: ResMut<NextState<PlayerState>>) {
+    if input.action_just_pressed(PlayerAction::Blueprint) {
+        next_player_state.set(PlayerState::Blueprint);
+    }
+}
+
+// Keys go to the name field while a region is selected
+fn stop_blueprint_mode(
+    input: InputParam,
+    tool: Res<BlueprintTool>,
+    mut next_player_state: ResMut<NextState<PlayerState>>,
+) {
+    if tool.selection.is_some() {
+        return;
+    }
+
+    if input.action_just_pressed(PlayerAction::Blueprint)
+        || input.action_just_pressed(PlayerAction::Cancel)
+    {
+        next_player_state.set(PlayerState::Normal);
+    }
+}
+
+fn reset_tool(mut tool: ResMut<BlueprintTool>) {
+    *tool = BlueprintTool::default();
+}
+
+fn ui_hovered(interactions: &Query<&Interaction>) -> bool {
+    interactions
+        .iter()
+        .any(|interaction| *interaction != Interaction::None)
+}
+
+// Dragging selects a region, right clicking clears it
+fn select_region(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    interactions: Query<&Interaction>,
+    mut tool: ResMut<BlueprintTool>,
+) {
+    if tool.stamping.is_some() {
+        return;
+    }
+
+    if mouse_input.just_pressed(MouseButton::Right) {
+        *tool = BlueprintTool::default();
+        return;
+    }
+
+    let Some(cell) = cursor_ground_position(&window, &camera).map(world_to_cell) else {
+        return;
+    };
+
+    if mouse_input.just_pressed(MouseButton::Left) && !ui_hovered(&interactions) {
+        tool.selection_start = Some(cell);
+        tool.selection = None;
+    }
+
+    if let Some(start) = tool.selection_start {
+        if mouse_input.just_released(MouseButton::Left) {
+            tool.selection_start = None;
+            tool.selection = Some((start.min(cell), start.max(cell)));
+        }
+    }
+}
+
+fn stamp_on_click(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    interactions: Query<&Interaction>,
+    mut tool: ResMut<BlueprintTool>,
+    mut stamp_event: EventWriter<StampBlueprintEvent>,
+) {
+    let Some(blueprint) = tool.stamping else {
+        return;
+    };
+
+    if mouse_input.just_pressed(MouseButton::Right) {
+        tool.stamping = None;
+        return;
+    }
+
+    if !mouse_input.just_pressed(MouseButton::Left) || ui_hovered(&interactions) {
+        return;
+    }
+
+    if let Some(origin) = cursor_ground_position(&window, &camera).map(world_to_cell) {
+        stamp_event.send(StampBlueprintEvent { blueprint, origin });
+    }
+}
+
+fn draw_blueprint_tool(
+    mut gizmos: Gizmos,
+    window: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    tool: Res<BlueprintTool>,
+    library: Res<BlueprintLibrary>,
+) {
+    let cursor_cell = cursor_ground_position(&window, &camera).map(world_to_cell);
+
+    if let Some(blueprint) = tool.stamping.and_then(|blueprint| library.get(blueprint)) {
+        let Some(origin) = cursor_cell else {
+            return;
+        };
+
+        for piece in &blueprint.pieces {
+            let transform = piece.transform(origin);
+            gizmos.cuboid(transform.with_scale(piece.kind.size()), SELECTION_COLOR);
+        }
+        return;
+    }
+
+    let region = match (tool.selection, tool.selection_start, cursor_cell) {
+        (Some(selection), _, _) => selection,
+        (None, Some(start), Some(cell)) => (start.min(cell), start.max(cell)),
+        _ => return,
+    };
+
+    let min = region.0.as_vec2() * CELL_SIZE;
+    let size = (region.1 - region.0 + IVec2::ONE).as_vec2() * CELL_SIZE;
+    gizmos.rect(
+        Vec3::new(
+            min.x + size.x / 2.0,
+            GROUND_LEVEL + 0.01,
+            min.y + size.y / 2.0,
+        ),
+        Quat::from_rotation_x(-FRAC_PI_2),
+        size,
+        SELECTION_COLOR,
+    );
+}
+
+fn save_blueprints(
+    buildings: Query<&Building>,
+    mut save_events: EventReader<SaveBlueprintEvent>,
+    mut library: ResMut<BlueprintLibrary>,
+) {
+    for save_event in save_events.read() {
+        let inside =
+            |cell: &IVec2| cell.cmpge(save_event.min).all() && cell.cmple(save_event.max).all();
+
+        let pieces: Vec<_> = buildings
+            .iter()
+            .filter(|building| building.cells.iter().all(inside))
+            .filter_map(|building| {
+                let anchor = building.cells.iter().copied().reduce(IVec2::min)?;
+                Some(BlueprintPiece {
+                    cell: (anchor - save_event.min).into(),
+                    kind: building.kind,
+                    quarter_turns: building.quarter_turns,
+                })
+            })
+            .collect();
+
+        if pieces.is_empty() {
+            info!("No buildings in the selected region");
+            continue;
+        }
+
+        let blueprint = Blueprint {
+            name: save_event.name.clone(),
+            pieces,
+        };
+        match library.save(blueprint) {
+            Ok(()) => info!("Saved blueprint {}", save_event.name),
+            Err(error) => error!("Could not save blueprint {}: {}", save_event.name, error),
+        }
+    }
+}
+
+fn stamp_blueprints(
+    mut commands: Commands,
+    mut stamp_events: EventReader<StampBlueprintEvent>,
+    library: Res<BlueprintLibrary>,
+    building_assets: Res<BuildingAssets>,
+    ghost_material: Res<GhostMaterial>,
+) {
+    for stamp_event in stamp_events.read() {
+        let Some(blueprint) = library.get(stamp_event.blueprint) else {
+            continue;
+        };
+
+        for piece in &blueprint.pieces {
+            commands
+                .spawn(PbrBundle {
+                    mesh: building_assets.mesh(piece.kind),
+                    material: ghost_material.0.clone(),
+                    transform: piece.transform(stamp_event.origin),
+                    ..Default::default()
+                })
+                .insert((
+                    BlueprintGhost { kind: piece.kind },
+                    Name::new(format!("{} ghost", piece.kind.name())),
+                ));
+        }
+    }
+}
+
+// Builds the nearest ghost the player can afford and reach, one at a time
+fn fill_ghosts(
+    mut commands: Commands,
+    mut player: Query<(&Transform, &mut Inventory), With<Player>>,
+    ghosts: Query<(Entity, &BlueprintGhost, &Transform)>,
+    placement_checker: PlacementChecker,
+    building_assets: Res<BuildingAssets>,
+    mut build_event: EventWriter<BuildEvent>,
+) {
+    let Ok((player_transform, mut inventory)) = player.get_single_mut() else {
+        return;
+    };
+    let player_position = player_transform.translation;
+
+    let mut ghosts: Vec<_> = ghosts.iter().collect();
+    ghosts.sort_by(|(_, _, transform_1), (_, _, transform_2)| {
+        let distance_1 = transform_1.translation.distance_squared(player_position);
+        let distance_2 = transform_2.translation.distance_squared(player_position);
+        distance_1.total_cmp(&distance_2)
+    });
+
+    for (entity, ghost, transform) in ghosts {
+        let footprint = rotated_footprint(ghost.kind.footprint(), transform.rotation);
+        match placement_checker.check(
+            ghost.kind,
+            footprint,
+            transform.translation,
+            player_position,
+        ) {
+            None => {}
+            // Leaving the ground means it can never be built
+            Some(PlacementError::OutOfBounds) => {
+                commands.entity(entity).despawn();
+                continue;
+            }
+            Some(_) => continue,
+        }
+
+        let placed = place_buildings(
+            &mut commands,
+            &mut inventory,
+            &building_assets,
+            ghost.kind,
+            [*transform],
+        );
+        if placed.is_empty() {
+            continue;
+        }
+
+        commands.entity(entity).despawn();
+        build_event.send(BuildEvent {
+            kind: ghost.kind,
+            placed,
+        });
+        return;
+    }
+}
@@ -0,0 +1,279 @@
+use bevy::{
+    color::palettes,
+    input::keyboard::{Key, KeyboardInput},
+    prelude::*,
+};
+
+use crate::{
+    blueprint::{BlueprintGhost, BlueprintLibrary, BlueprintTool, SaveBlueprintEvent},
+    player::PlayerState,
+    player_input::{InputMap, PlayerAction},
+};
+
+const MAX_NAME_LENGTH: usize = 24;
+const BUTTON_COLOR: Color = Color::srgb(0.25, 0.25, 0.25);
+const HOVERED_COLOR: Color = Color::srgb(0.32, 0.32, 0.32);
+const SELECTED_COLOR: Color = Color::srgb(0.25, 0.35, 0.5);
+
+pub struct BlueprintUiPlugin;
+
+impl Plugin for BlueprintUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(PlayerState::Blueprint),
+            (spawn_blueprint_panel, fill_blueprint_list).chain(),
+        )
+        .add_systems(OnExit(PlayerState::Blueprint), despawn_blueprint_panel)
+        .add_systems(
+            Update,
+            (
+                type_blueprint_name,
+                fill_blueprint_list.run_if(resource_changed::<BlueprintLibrary>),
+                press_blueprint_buttons,
+                press_clear_ghosts_button,
+                update_blueprint_buttons,
+                update_status_text,
+            )
+                .chain()
+                .run_if(in_state(PlayerState::Blueprint)),
+        );
+    }
+}
+
+#[derive(Component)]
+struct BlueprintPanel;
+
+#[derive(Component)]
+struct BlueprintList;
+
+#[derive(Component)]
+struct BlueprintButton(usize);
+
+#[derive(Component)]
+struct ClearGhostsButton;
+
+#[derive(Component)]
+struct StatusText;
+
+fn text_style(font_size: f32) -> TextStyle {
+    TextStyle {
+        font_size,
+        color: palettes::basic::WHITE.into(),
+        ..Default::default()
+    }
+}
+
+fn button_bundle() -> ButtonBundle {
+    ButtonBundle {
+        style: Style {
+            padding: UiRect::all(Val::Px(4.0)),
+            ..Default::default()
+        },
+        background_color: BUTTON_COLOR.into(),
+        ..Default::default()
+    }
+}
+
+fn spawn_blueprint_panel(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(40.0),
+                left: Val::Px(8.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(8.0)),
+                row_gap: Val::Px(4.0),
+                width: Val::Px(260.0),
+                ..Default::default()
+            },
+            background_color: Color::srgba(0.15, 0.15, 0.15, 0.9).into(),
+            ..Default::default()
+        })
+        // Tracks hovering so clicks on the panel do not reach the world
+        .insert((BlueprintPanel, Interaction::default()))
+        .with_children(|builder| {
+            builder.spawn(TextBundle::from_section("Blueprints", text_style(24.0)));
+            builder.spawn((TextBundle::from_section("", text_style(14.0)), StatusText));
+
+            builder.spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(4.0),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                BlueprintList,
+            ));
+
+            builder
+                .spawn((button_bundle(), ClearGhostsButton))
+                .with_children(|builder| {
+                    builder.spawn(TextBundle::from_section("Clear ghosts", text_style(16.0)));
+                });
+        });
+}
+
+fn despawn_blueprint_panel(mut commands: Commands, panels: Query<Entity, With<BlueprintPanel>>) {
+    for panel in &panels {
+        commands.entity(panel).despawn_recursive();
+    }
+}
+
+fn fill_blueprint_list(
+    mut commands: Commands,
+    list: Query<Entity, With<BlueprintList>>,
+    library: Res<BlueprintLibrary>,
+) {
+    let Ok(list) = list.get_single() else {
+        return;
+    };
+
+    commands
+        .entity(list)
+        .despawn_descendants()
+        .with_children(|builder| {
+            for (index, blueprint) in library.iter() {
+                builder
+                    .spawn((button_bundle(), BlueprintButton(index)))
+                    .with_children(|builder| {
+                        builder.spawn(TextBundle::from_section(
+                            format!("{} ({} pieces)", blueprint.name, blueprint.pieces.len()),
+                            text_style(16.0),
+                        ));
+                    });
+            }
+        });
+}
+
+// Typing names the selected region, Enter saves it
+fn type_blueprint_name(
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut tool: ResMut<BlueprintTool>,
+    mut save_event: EventWriter<SaveBlueprintEvent>,
+) {
+    let Some((min, max)) = tool.selection else {
+        keyboard_events.clear();
+        return;
+    };
+
+    for keyboard_event in keyboard_events.read() {
+        if !keyboard_event.state.is_pressed() {
+            continue;
+        }
+
+        match &keyboard_event.logical_key {
+            Key::Character(characters) => {
+                for character in characters.chars() {
+                    let allowed = character.is_alphanumeric() || " -_".contains(character);
+                    if allowed && tool.name.len() < MAX_NAME_LENGTH {
+                        tool.name.push(character);
+                    }
+                }
+            }
+            Key::Space if tool.name.len() < MAX_NAME_LENGTH => tool.name.push(' '),
+            Key::Backspace => {
+                tool.name.pop();
+            }
+            Key::Enter if !tool.name.trim().is_empty() => {
+                save_event.send(SaveBlueprintEvent {
+                    name: tool.name.trim().to_string(),
+                    min,
+                    max,
+                });
+                tool.name.clear();
+                tool.selection = None;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn press_blueprint_buttons(
+    buttons: Query<(&Interaction, &BlueprintButton), Changed<Interaction>>,
+    mut tool: ResMut<BlueprintTool>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction == Interaction::Pressed {
+            tool.selection = None;
+            tool.name.clear();
+            tool.stamping = Some(button.0);
+        }
+    }
+}
+
+fn press_clear_ghosts_button(
+    mut commands: Commands,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<ClearGhostsButton>)>,
+    ghosts: Query<Entity, With<BlueprintGhost>>,
+) {
+    if !buttons
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        return;
+    }
+
+    for ghost in &ghosts {
+        commands.entity(ghost).despawn();
+    }
+}
+
+fn update_blueprint_buttons(
+    mut buttons: Query<
+        (&Interaction, &mut BackgroundColor, Option<&BlueprintButton>),
+        With<Button>,
+    >,
+    tool: Res<BlueprintTool>,
+) {
+    for (interaction, mut background_color, blueprint_button) in &mut buttons {
+        let selected = blueprint_button.is_some_and(|button| tool.stamping == Some(button.0));
+        let color = if selected {
+            SELECTED_COLOR
+        } else if *interaction == Interaction::Hovered {
+            HOVERED_COLOR
+        } else {
+            BUTTON_COLOR
+        };
+
+        if background_color.0 != color {
+            background_color.0 = color;
+        }
+    }
+}
+
+fn update_status_text(
+    mut status_text: Query<&mut Text, With<StatusText>>,
+    tool: Res<BlueprintTool>,
+    library: Res<BlueprintLibrary>,
+    input_map: Res<InputMap>,
+) {
+    let Ok(mut status_text) = status_text.get_single_mut() else {
+        return;
+    };
+
+    let value = if let Some(blueprint) = tool.stamping.and_then(|index| library.get(index)) {
+        format!(
+            "Stamping {}\n[Click] place ghost  [Right click] stop",
+            blueprint.name
+        )
+    } else if let Some((min, max)) = tool.selection {
+        let size = max - min + IVec2::ONE;
+        format!(
+            "Selected {}x{}\nName: {}_\n[Enter] save  [Right click] clear",
+            size.x, size.y, tool.name
+        )
+    } else {
+        format!(
+            "[Drag] select a region  [{}] [{}] done",
+            input_map.key_label(PlayerAction::Blueprint),
+            input_map.key_label(PlayerAction::Cancel)
+        )
+    };
+
+    if status_text.sections[0].value != value {
+        status_text.sections[0].value = value;
+    }
+}
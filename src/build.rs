@@ -1,138 +1,613 @@
 use core::f32;
-use std::f32::consts::PI;
+use std::f32::consts::{FRAC_PI_2, PI};
 
-use bevy::{color::palettes, prelude::*};
+use bevy::{
+    ecs::system::SystemParam, input::common_conditions::input_just_released, prelude::*,
+    window::PrimaryWindow,
+};
 use bevy_rapier3d::prelude::*;
 
 use crate::{
+    blueprint::BlueprintPlugin,
+    build_history::BuildHistoryPlugin,
+    build_menu::BuildMenuPlugin,
+    building::{Building, BuildingAssets, BuildingKind, BuildingLayer, BuildingPlugin},
+    building_grid::{footprint_cells, BuildingGrid},
+    construction::spawn_construction_site,
+    deconstruct::DeconstructPlugin,
     inventory::Inventory,
-    player::PlayerState,
-    player_input::{InputMap, PlayerAction},
+    player::{Facing, Player, PlayerState},
+    player_input::{InputParam, PlayerAction},
 };
 
-const CELL_SIZE: f32 = 1.0;
+pub const CELL_SIZE: f32 = 1.0;
+const VALID_PREVIEW_COLOR: Color = Color::srgba(0.3, 0.9, 0.4, 0.4);
+const INVALID_PREVIEW_COLOR: Color = Color::srgba(1.0, 0.25, 0.25, 0.4);
+const PREVIEW_DISTANCE: f32 = 2.0;
+pub const MAX_BUILD_DISTANCE: f32 = 8.0;
+pub const GROUND_LEVEL: f32 = 0.1;
+const GROUND_HALF_EXTENT: f32 = 50.0;
+// Shrinks the clearance check so neighbouring buildings may touch
+const PLACEMENT_MARGIN: f32 = 0.05;
+// Pieces a drag may span along each axis beyond its start
+const MAX_DRAG_SPAN: i32 = 10;
 
 pub struct BuildPlugin;
 
 impl Plugin for BuildPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(PlayerState::BuildingMode), enter_build_mode)
-            .add_systems(OnExit(PlayerState::BuildingMode), exit_building_mode)
-            .add_systems(
-                Update,
-                (move_preview, build, draw_building_grid)
-                    .run_if(in_state(PlayerState::BuildingMode)),
-            );
+        app.add_plugins((
+            BuildingPlugin,
+            BuildMenuPlugin,
+            BuildHistoryPlugin,
+            DeconstructPlugin,
+            BlueprintPlugin,
+        ))
+        .init_resource::<SelectedBuilding>()
+        .init_resource::<DragPlacement>()
+        .add_event::<BuildEvent>()
+        .add_systems(Startup, setup)
+        .add_systems(OnEnter(PlayerState::BuildingMode), enter_build_mode)
+        .add_systems(OnExit(PlayerState::BuildingMode), exit_building_mode)
+        .add_systems(
+            Update,
+            (
+                select_building,
+                replace_preview.run_if(resource_changed::<SelectedBuilding>),
+                rotate_preview,
+                move_preview,
+                start_drag,
+                update_drag,
+                validate_previews,
+                build,
+                finish_drag.run_if(input_just_released(MouseButton::Left)),
+                draw_building_grid,
+            )
+                .chain()
+                .run_if(in_state(PlayerState::BuildingMode)),
+        );
     }
 }
 
-#[derive(Component)]
-struct BuildingPreview;
+#[derive(Resource, Default)]
+pub struct SelectedBuilding(pub usize);
+
+impl SelectedBuilding {
+    pub fn kind(&self) -> BuildingKind {
+        BuildingKind::ALL[self.0]
+    }
+}
+
+// Buildings the player placed together in one go
+#[derive(Event)]
+pub struct BuildEvent {
+    pub kind: BuildingKind,
+    pub placed: Vec<(Entity, Transform)>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PlacementError {
+    Occupied,
+    Blocked,
+    TooFar,
+    OutOfBounds,
+}
+
+impl PlacementError {
+    pub fn reason(&self) -> &'static str {
+        match self {
+            PlacementError::Occupied => "Another building is already there",
+            PlacementError::Blocked => "Something is in the way",
+            PlacementError::TooFar => "Too far away",
+            PlacementError::OutOfBounds => "Outside of the buildable area",
+        }
+    }
+}
+
+// The preview following the cursor. Rotation is kept in quarter turns so
+// footprints stay aligned to the grid.
+#[derive(Component, Default)]
+pub struct BuildingPreview {
+    quarter_turns: u32,
+}
+
+impl BuildingPreview {
+    fn rotation(&self) -> Quat {
+        Quat::from_rotation_y(self.quarter_turns as f32 * FRAC_PI_2)
+    }
 
+    fn footprint(&self, kind: BuildingKind) -> UVec2 {
+        let footprint = kind.footprint();
+        if self.quarter_turns % 2 == 1 {
+            UVec2::new(footprint.y, footprint.x)
+        } else {
+            footprint
+        }
+    }
+}
+
+// One of the pieces laid out by dragging
 #[derive(Component)]
-struct Building;
+struct DragPreview;
+
+// Whether a preview, cursor or dragged, can be built where it stands
+#[derive(Component, Default)]
+pub struct PreviewPlacement {
+    pub error: Option<PlacementError>,
+}
+
+#[derive(Resource, Default)]
+pub struct DragPlacement {
+    start: Option<Vec3>,
+    pub positions: Vec<Vec3>,
+    pub valid: usize,
+}
+
+impl DragPlacement {
+    pub fn is_dragging(&self) -> bool {
+        self.start.is_some()
+    }
+}
+
+#[derive(Resource)]
+struct PreviewMaterials {
+    valid: Handle<StandardMaterial>,
+    invalid: Handle<StandardMaterial>,
+}
+
+impl PreviewMaterials {
+    fn get(&self, error: Option<PlacementError>) -> Handle<StandardMaterial> {
+        match error {
+            Some(_) => self.invalid.clone(),
+            None => self.valid.clone(),
+        }
+    }
+}
+
+#[derive(SystemParam)]
+pub struct PlacementChecker<'w, 's> {
+    rapier_context: Res<'w, RapierContext>,
+    obstacles: Query<'w, 's, (), (Without<Sensor>, Without<Building>)>,
+    building_grid: Res<'w, BuildingGrid>,
+}
+
+impl<'w, 's> PlacementChecker<'w, 's> {
+    pub fn check(
+        &self,
+        kind: BuildingKind,
+        footprint: UVec2,
+        center: Vec3,
+        player_position: Vec3,
+    ) -> Option<PlacementError> {
+        let half_size = footprint.as_vec2() * CELL_SIZE / 2.0;
+        if center.x.abs() + half_size.x > GROUND_HALF_EXTENT
+            || center.z.abs() + half_size.y > GROUND_HALF_EXTENT
+        {
+            return Some(PlacementError::OutOfBounds);
+        }
+
+        if center.xz().distance(player_position.xz()) > MAX_BUILD_DISTANCE {
+            return Some(PlacementError::TooFar);
+        }
+
+        if !self
+            .building_grid
+            .is_free(&footprint_cells(footprint, center), kind.layer())
+        {
+            return Some(PlacementError::Occupied);
+        }
+
+        // Floors can be laid under anything that is not another floor
+        if kind.layer() == BuildingLayer::Floor {
+            return None;
+        }
+
+        let blocks = |entity| self.obstacles.contains(entity);
+        let half_height = kind.size().y / 2.0 - PLACEMENT_MARGIN;
+        let shape = Collider::cuboid(
+            half_size.x - PLACEMENT_MARGIN,
+            half_height,
+            half_size.y - PLACEMENT_MARGIN,
+        );
+        let blocker = self.rapier_context.intersection_with_shape(
+            center,
+            Quat::IDENTITY,
+            &shape,
+            QueryFilter::new().predicate(&blocks),
+        );
+
+        blocker.map(|_| PlacementError::Blocked)
+    }
+}
+
+// Point of the ground under the cursor, if any
+pub fn cursor_ground_position(
+    window: &Query<&Window, With<PrimaryWindow>>,
+    camera: &Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+) -> Option<Vec3> {
+    let cursor_position = window.get_single().ok()?.cursor_position()?;
+    let (camera, camera_transform) = camera.get_single().ok()?;
+    let ray = camera.viewport_to_world(camera_transform, cursor_position)?;
+    let distance = ray.intersect_plane(Vec3::Y * GROUND_LEVEL, InfinitePlane3d::new(Vec3::Y))?;
+
+    Some(ray.get_point(distance))
+}
+
+// Snaps the footprint to the cells around `position` and returns its centre
+fn footprint_center(footprint: UVec2, height: f32, position: Vec3) -> Vec3 {
+    let size = footprint.as_vec2() * CELL_SIZE;
+    let corner = Vec2::new(position.x, position.z) - (size - CELL_SIZE) / 2.0;
+    let corner = (corner / CELL_SIZE).floor() * CELL_SIZE;
+
+    Vec3::new(
+        corner.x + size.x / 2.0,
+        GROUND_LEVEL + height / 2.0,
+        corner.y + size.y / 2.0,
+    )
+}
+
+// Positions from `start` towards `end`, a rectangle for floors and a straight
+// line along the longest axis otherwise
+fn drag_positions(kind: BuildingKind, footprint: UVec2, start: Vec3, end: Vec3) -> Vec<Vec3> {
+    let step = footprint.as_vec2() * CELL_SIZE;
+    let mut steps = ((end - start).xz() / step).round().as_ivec2();
+
+    if kind.layer() != BuildingLayer::Floor {
+        if steps.x.abs() >= steps.y.abs() {
+            steps.y = 0;
+        } else {
+            steps.x = 0;
+        }
+    }
+
+    let steps = steps.clamp(IVec2::splat(-MAX_DRAG_SPAN), IVec2::splat(MAX_DRAG_SPAN));
+    let direction = steps.signum().as_vec2() * step;
+
+    (0..=steps.x.abs())
+        .flat_map(|x| {
+            (0..=steps.y.abs()).map(move |z| {
+                start + Vec3::new(x as f32 * direction.x, 0.0, z as f32 * direction.y)
+            })
+        })
+        .collect()
+}
+
+// Pays for and lays out each construction site in turn, stopping once
+// resources run out
+pub fn place_buildings(
+    commands: &mut Commands,
+    inventory: &mut Inventory,
+    building_assets: &BuildingAssets,
+    kind: BuildingKind,
+    transforms: impl IntoIterator<Item = Transform>,
+) -> Vec<(Entity, Transform)> {
+    let cost = kind.cost();
+    let mut placed = Vec::new();
+
+    for transform in transforms {
+        if !cost
+            .iter()
+            .all(|(item, amount)| inventory.has(*item, *amount))
+        {
+            break;
+        }
+        for (item, amount) in &cost {
+            inventory.remove(*item, *amount);
+        }
+
+        let building = spawn_construction_site(commands, building_assets, kind, transform);
+        placed.push((building, transform));
+    }
+
+    placed
+}
+
+fn setup(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
+    commands.insert_resource(PreviewMaterials {
+        valid: materials.add(StandardMaterial::from_color(VALID_PREVIEW_COLOR)),
+        invalid: materials.add(StandardMaterial::from_color(INVALID_PREVIEW_COLOR)),
+    });
+}
 
 fn enter_build_mode(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    player: Query<(&Transform, &Facing), With<Player>>,
+    selected_building: Res<SelectedBuilding>,
+    building_assets: Res<BuildingAssets>,
+    preview_materials: Res<PreviewMaterials>,
 ) {
+    let Ok((player_transform, facing)) = player.get_single() else {
+        return;
+    };
+
+    let kind = selected_building.kind();
+    let preview = BuildingPreview::default();
+    let position = player_transform.translation + facing.0 * PREVIEW_DISTANCE;
+
     commands
         .spawn(PbrBundle {
-            mesh: meshes.add(Cuboid::from_size(Vec3 {
-                x: 2.0,
-                y: 1.5,
-                z: 2.0,
-            })),
-            material: materials.add(StandardMaterial::from_color(
-                palettes::basic::MAROON.with_alpha(0.3),
+            mesh: building_assets.mesh(kind),
+            material: preview_materials.valid.clone(),
+            transform: Transform::from_translation(footprint_center(
+                preview.footprint(kind),
+                kind.size().y,
+                position,
             )),
-            transform: Transform::from_xyz(0.0, 0.75, 0.0),
             ..Default::default()
         })
-        .insert(BuildingPreview);
+        .insert((preview, PreviewPlacement::default()));
+}
+
+fn select_building(input: InputParam, mut selected_building: ResMut<SelectedBuilding>) {
+    if input.action_just_pressed(PlayerAction::NextBuilding) {
+        selected_building.0 = (selected_building.0 + 1) % BuildingKind::ALL.len();
+    }
+}
+
+fn replace_preview(
+    mut preview: Query<&mut Handle<Mesh>, With<BuildingPreview>>,
+    selected_building: Res<SelectedBuilding>,
+    building_assets: Res<BuildingAssets>,
+) {
+    if let Ok(mut mesh) = preview.get_single_mut() {
+        *mesh = building_assets.mesh(selected_building.kind());
+    }
+}
+
+fn rotate_preview(input: InputParam, mut preview: Query<&mut BuildingPreview>) {
+    if !input.action_just_pressed(PlayerAction::RotateBuilding) {
+        return;
+    }
+
+    if let Ok(mut preview) = preview.get_single_mut() {
+        preview.quarter_turns = (preview.quarter_turns + 1) % 4;
+    }
 }
 
 fn exit_building_mode(
     mut commands: Commands,
-    building_preview: Query<
-        (Entity, &Handle<Mesh>, &Handle<StandardMaterial>),
-        With<BuildingPreview>,
-    >,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    previews: Query<Entity, With<PreviewPlacement>>,
+    mut drag: ResMut<DragPlacement>,
 ) {
-    for (entity, mesh_handle, material_handle) in &building_preview {
+    for entity in &previews {
         commands.entity(entity).despawn();
-        meshes.remove(mesh_handle);
-        materials.remove(material_handle);
     }
+    *drag = DragPlacement::default();
 }
 
+// Follows the point of the ground under the cursor, keeping the last cell when
+// the cursor leaves the window
 fn move_preview(
-    input: Res<ButtonInput<KeyCode>>,
-    mut preview: Query<&mut Transform, With<BuildingPreview>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    mut preview: Query<(&BuildingPreview, &mut Transform)>,
+    selected_building: Res<SelectedBuilding>,
 ) {
-    let mut direction = Vec3::ZERO;
+    let Ok((preview, mut preview_transform)) = preview.get_single_mut() else {
+        return;
+    };
+    let kind = selected_building.kind();
+
+    preview_transform.rotation = preview.rotation();
+
+    if let Some(position) = cursor_ground_position(&window, &camera) {
+        preview_transform.translation =
+            footprint_center(preview.footprint(kind), kind.size().y, position);
+    }
+}
+
+// Clicks on the build menu do not start a drag
+fn start_drag(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    interactions: Query<&Interaction>,
+    preview: Query<&Transform, With<BuildingPreview>>,
+    mut drag: ResMut<DragPlacement>,
+) {
+    if !mouse_input.just_pressed(MouseButton::Left) {
+        return;
+    }
 
-    if input.just_pressed(KeyCode::KeyW) {
-        direction = -Vec3::Z;
+    if interactions
+        .iter()
+        .any(|interaction| *interaction != Interaction::None)
+    {
+        return;
+    }
+
+    if let Ok(preview_transform) = preview.get_single() {
+        drag.start = Some(preview_transform.translation);
     }
-    if input.just_pressed(KeyCode::KeyS) {
-        direction = Vec3::Z;
+}
+
+// Lays out a preview per piece between the drag start and the cursor, hiding
+// the cursor preview meanwhile
+fn update_drag(
+    mut commands: Commands,
+    mut preview: Query<(Ref<BuildingPreview>, &Transform, &mut Visibility)>,
+    drag_previews: Query<Entity, With<DragPreview>>,
+    mut drag: ResMut<DragPlacement>,
+    selected_building: Res<SelectedBuilding>,
+    building_assets: Res<BuildingAssets>,
+    preview_materials: Res<PreviewMaterials>,
+) {
+    let Ok((preview, preview_transform, mut visibility)) = preview.get_single_mut() else {
+        return;
+    };
+
+    let Some(start) = drag.start else {
+        visibility.set_if_neq(Visibility::Inherited);
+        return;
+    };
+    visibility.set_if_neq(Visibility::Hidden);
+
+    let kind = selected_building.kind();
+    let positions = drag_positions(
+        kind,
+        preview.footprint(kind),
+        start,
+        preview_transform.translation,
+    );
+
+    if positions == drag.positions && !preview.is_changed() && !selected_building.is_changed() {
+        return;
     }
-    if input.just_pressed(KeyCode::KeyA) {
-        direction = -Vec3::X;
+
+    for entity in &drag_previews {
+        commands.entity(entity).despawn();
     }
-    if input.just_pressed(KeyCode::KeyD) {
-        direction = Vec3::X;
+
+    for position in &positions {
+        commands
+            .spawn(PbrBundle {
+                mesh: building_assets.mesh(kind),
+                material: preview_materials.valid.clone(),
+                transform: Transform::from_translation(*position).with_rotation(preview.rotation()),
+                ..Default::default()
+            })
+            .insert((DragPreview, PreviewPlacement::default()));
     }
 
-    let mut preview_transform = preview.single_mut();
-    preview_transform.translation += direction * CELL_SIZE;
+    drag.positions = positions;
+}
+
+// Tints previews green when they can be placed and red otherwise
+fn validate_previews(
+    player: Query<&Transform, With<Player>>,
+    cursor_preview: Query<&BuildingPreview>,
+    mut previews: Query<(
+        &mut PreviewPlacement,
+        &Transform,
+        &mut Handle<StandardMaterial>,
+        Has<DragPreview>,
+    )>,
+    mut drag: ResMut<DragPlacement>,
+    placement_checker: PlacementChecker,
+    selected_building: Res<SelectedBuilding>,
+    preview_materials: Res<PreviewMaterials>,
+) {
+    let Ok(player_transform) = player.get_single() else {
+        return;
+    };
+    let Ok(cursor_preview) = cursor_preview.get_single() else {
+        return;
+    };
+
+    let kind = selected_building.kind();
+    let footprint = cursor_preview.footprint(kind);
+    let mut valid = 0;
+
+    for (mut placement, preview_transform, mut material, is_dragged) in &mut previews {
+        let error = placement_checker.check(
+            kind,
+            footprint,
+            preview_transform.translation,
+            player_transform.translation,
+        );
+
+        if is_dragged && error.is_none() {
+            valid += 1;
+        }
+
+        if placement.error != error {
+            placement.error = error;
+            *material = preview_materials.get(error);
+        }
+    }
+
+    if drag.valid != valid {
+        drag.valid = valid;
+    }
 }
 
 fn build(
-    input: Res<ButtonInput<KeyCode>>,
-    input_map: Res<InputMap>,
-    mut inventory: ResMut<Inventory>,
+    input: InputParam,
     mut commands: Commands,
-    preview: Query<&Transform, With<BuildingPreview>>,
-    mut next_player_state: ResMut<NextState<PlayerState>>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut inventory: Query<&mut Inventory, With<Player>>,
+    preview: Query<(&PreviewPlacement, &Transform), With<BuildingPreview>>,
+    selected_building: Res<SelectedBuilding>,
+    building_assets: Res<BuildingAssets>,
+    mut build_event: EventWriter<BuildEvent>,
 ) {
-    let Some(key_code) = input_map.map.get(&PlayerAction::Build) else {
+    if !input.action_just_pressed(PlayerAction::Build) {
+        return;
+    }
+
+    let Ok((placement, preview_transform)) = preview.get_single() else {
         return;
     };
-    if !input.just_pressed(*key_code) {
+
+    let kind = selected_building.kind();
+    if let Some(placement_error) = placement.error {
+        info!(
+            "Cannot build a {} here: {}",
+            kind.name(),
+            placement_error.reason()
+        );
+        return;
+    }
+
+    let placed = place_buildings(
+        &mut commands,
+        &mut inventory.single_mut(),
+        &building_assets,
+        kind,
+        [*preview_transform],
+    );
+
+    if placed.is_empty() {
+        info!("Not enough resources to build a {}", kind.name());
         return;
     }
 
-    let cost = 30;
-    if cost > inventory.wood {
+    build_event.send(BuildEvent { kind, placed });
+}
+
+// Releasing the mouse builds every placeable piece of the drag
+fn finish_drag(
+    mut commands: Commands,
+    mut inventory: Query<&mut Inventory, With<Player>>,
+    drag_previews: Query<(Entity, &PreviewPlacement, &Transform), With<DragPreview>>,
+    mut drag: ResMut<DragPlacement>,
+    selected_building: Res<SelectedBuilding>,
+    building_assets: Res<BuildingAssets>,
+    mut build_event: EventWriter<BuildEvent>,
+) {
+    if !drag.is_dragging() {
         return;
     }
 
-    inventory.wood -= cost;
+    let kind = selected_building.kind();
+    let transforms: Vec<_> = drag_previews
+        .iter()
+        .filter(|(_, placement, _)| placement.error.is_none())
+        .map(|(_, _, transform)| *transform)
+        .collect();
 
-    let preview_transform = preview.single();
+    let placed = place_buildings(
+        &mut commands,
+        &mut inventory.single_mut(),
+        &building_assets,
+        kind,
+        transforms,
+    );
 
-    commands
-        .spawn(PbrBundle {
-            mesh: meshes.add(Cuboid::from_size(Vec3 {
-                x: 2.0,
-                y: 1.5,
-                z: 2.0,
-            })),
-            material: materials.add(StandardMaterial::from_color(palettes::basic::MAROON)),
-            transform: *preview_transform,
-            ..Default::default()
-        })
-        .insert((RigidBody::Fixed, Collider::cuboid(1.0, 0.75, 1.0)))
-        .insert(Building);
+    if placed.len() < drag.positions.len() {
+        info!(
+            "Built {} of {} {} pieces",
+            placed.len(),
+            drag.positions.len(),
+            kind.name()
+        );
+    }
 
-    next_player_state.set(PlayerState::Normal);
+    for (entity, _, _) in &drag_previews {
+        commands.entity(entity).despawn();
+    }
+    *drag = DragPlacement::default();
+
+    if !placed.is_empty() {
+        build_event.send(BuildEvent { kind, placed });
+    }
 }
 
 fn draw_building_grid(mut gizmos: Gizmos) {
@@ -146,3 +621,84 @@ fn draw_building_grid(mut gizmos: Gizmos) {
         )
         .outer_edges();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drag_positions_follow_the_longest_axis() {
+        let start = Vec3::new(0.5, 0.0, 0.5);
+        let positions = drag_positions(
+            BuildingKind::Wall,
+            UVec2::ONE,
+            start,
+            Vec3::new(3.4, 0.0, 1.6),
+        );
+
+        assert_eq!(
+            positions,
+            vec![
+                start,
+                Vec3::new(1.5, 0.0, 0.5),
+                Vec3::new(2.5, 0.0, 0.5),
+                Vec3::new(3.5, 0.0, 0.5),
+            ]
+        );
+    }
+
+    #[test]
+    fn drag_positions_clamp_to_max_span() {
+        let start = Vec3::new(0.5, 0.0, 0.5);
+        let positions = drag_positions(
+            BuildingKind::Fence,
+            UVec2::ONE,
+            start,
+            Vec3::new(0.5, 0.0, -40.5),
+        );
+
+        assert_eq!(positions.len(), MAX_DRAG_SPAN as usize + 1);
+        assert_eq!(
+            positions.last(),
+            Some(&Vec3::new(0.5, 0.0, 0.5 - MAX_DRAG_SPAN as f32))
+        );
+    }
+
+    #[test]
+    fn drag_positions_fill_a_clamped_rectangle_for_floors() {
+        let start = Vec3::new(0.5, 0.0, 0.5);
+
+        let positions = drag_positions(
+            BuildingKind::Floor,
+            UVec2::ONE,
+            start,
+            Vec3::new(3.5, 0.0, 2.5),
+        );
+        assert_eq!(positions.len(), 4 * 3);
+
+        let positions = drag_positions(
+            BuildingKind::Floor,
+            UVec2::ONE,
+            start,
+            Vec3::new(50.0, 0.0, -50.0),
+        );
+        let span = MAX_DRAG_SPAN as usize + 1;
+        assert_eq!(positions.len(), span * span);
+    }
+
+    #[test]
+    fn drag_positions_step_by_the_footprint() {
+        let start = Vec3::new(1.0, 0.0, 0.5);
+        let positions = drag_positions(
+            BuildingKind::Workbench,
+            UVec2::new(2, 1),
+            start,
+            Vec3::new(5.0, 0.0, 0.5),
+        );
+
+        assert_eq!(
+            positions,
+            vec![start, Vec3::new(3.0, 0.0, 0.5), Vec3::new(5.0, 0.0, 0.5)]
+        );
+    }
+}
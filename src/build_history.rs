@@ -0,0 +1,355 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    build::{BuildEvent, PlacementChecker, PlacementError},
+    building::{spawn_building, Building, BuildingAssets, BuildingKind},
+    building_grid::rotated_footprint,
+    construction::{spawn_construction_site, spawn_partial_construction_site, ConstructionSite},
+    deconstruct::{deconstruct_buildings, DeconstructEvent, DeconstructRefund},
+    inventory::Inventory,
+    item::{ItemId, ItemRegistry},
+    pickup::DropItemEvent,
+    player::{Player, PlayerState},
+    player_input::{InputParam, PlayerAction},
+};
+
+const MAX_HISTORY: usize = 50;
+// Seconds after placing during which undoing gives back the full cost
+const UNDO_REFUND_WINDOW: f32 = 30.0;
+
+pub struct BuildHistoryPlugin;
+
+impl Plugin for BuildHistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BuildHistory>().add_systems(
+            Update,
+            (
+                record_builds.run_if(on_event::<BuildEvent>()),
+                record_deconstructions
+                    .before(deconstruct_buildings)
+                    .run_if(on_event::<DeconstructEvent>()),
+                (undo, redo)
+                    .chain()
+                    .run_if(in_state(PlayerState::BuildingMode)),
+            ),
+        );
+    }
+}
+
+#[derive(Clone, Copy)]
+struct HistoryPiece {
+    entity: Entity,
+    kind: BuildingKind,
+    transform: Transform,
+    // Work put into the piece while it was still a construction site
+    site_progress: Option<f32>,
+}
+
+enum BuildOperation {
+    Build {
+        pieces: Vec<HistoryPiece>,
+        placed_at: f32,
+    },
+    Deconstruct {
+        piece: HistoryPiece,
+        refund: Vec<(ItemId, u32)>,
+    },
+}
+
+impl BuildOperation {
+    fn pieces_mut(&mut self) -> impl Iterator<Item = &mut HistoryPiece> {
+        match self {
+            BuildOperation::Build { pieces, .. } => pieces.iter_mut(),
+            BuildOperation::Deconstruct { piece, .. } => std::slice::from_mut(piece).iter_mut(),
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct BuildHistory {
+    undo: Vec<BuildOperation>,
+    redo: Vec<BuildOperation>,
+}
+
+impl BuildHistory {
+    fn push(&mut self, operation: BuildOperation) {
+        self.redo.clear();
+        self.undo.push(operation);
+        if self.undo.len() > MAX_HISTORY {
+            self.undo.remove(0);
+        }
+    }
+
+    // Respawned buildings get new entities, so older operations are pointed
+    // at the replacement
+    fn replace_entity(&mut self, old: Entity, new: Entity) {
+        for operation in self.undo.iter_mut().chain(self.redo.iter_mut()) {
+            for piece in operation.pieces_mut() {
+                if piece.entity == old {
+                    piece.entity = new;
+                }
+            }
+        }
+    }
+}
+
+#[derive(SystemParam)]
+struct BuildOperations<'w, 's> {
+    commands: Commands<'w, 's>,
+    player: Query<'w, 's, (Entity, &'static Transform, &'static mut Inventory), With<Player>>,
+    buildings: Query<'w, 's, &'static Building>,
+    placement_checker: PlacementChecker<'w, 's>,
+    building_assets: Res<'w, BuildingAssets>,
+    registry: Res<'w, ItemRegistry>,
+    drop_event: EventWriter<'w, DropItemEvent>,
+}
+
+impl<'w, 's> BuildOperations<'w, 's> {
+    // Distance is ignored, the player may have walked off since
+    fn can_place(&self, pieces: &[HistoryPiece]) -> bool {
+        let Ok((_, player_transform, _)) = self.player.get_single() else {
+            return false;
+        };
+
+        pieces.iter().all(|piece| {
+            let footprint = rotated_footprint(piece.kind.footprint(), piece.transform.rotation);
+            matches!(
+                self.placement_checker.check(
+                    piece.kind,
+                    footprint,
+                    piece.transform.translation,
+                    player_transform.translation,
+                ),
+                None | Some(PlacementError::TooFar)
+            )
+        })
+    }
+
+    fn restore(&mut self, piece: &HistoryPiece) -> Entity {
+        match piece.site_progress {
+            Some(progress) => spawn_partial_construction_site(
+                &mut self.commands,
+                &self.building_assets,
+                piece.kind,
+                piece.transform,
+                ConstructionSite { progress },
+            ),
+            None => spawn_building(
+                &mut self.commands,
+                &self.building_assets,
+                piece.kind,
+                piece.transform,
+            ),
+        }
+    }
+
+    fn rebuild(&mut self, piece: &HistoryPiece) -> Entity {
+        spawn_construction_site(
+            &mut self.commands,
+            &self.building_assets,
+            piece.kind,
+            piece.transform,
+        )
+    }
+
+    // Removes the pieces that still stand and returns what they cost
+    fn despawn(&mut self, pieces: &[HistoryPiece]) -> Vec<(ItemId, u32)> {
+        let mut cost = Vec::new();
+        for piece in pieces {
+            if !self.buildings.contains(piece.entity) {
+                continue;
+            }
+            self.commands.entity(piece.entity).despawn_recursive();
+            cost.extend(piece.kind.cost());
+        }
+        cost
+    }
+
+    fn give(&mut self, items: &[(ItemId, u32)]) {
+        let Ok((actor, _, mut inventory)) = self.player.get_single_mut() else {
+            return;
+        };
+
+        for (item, amount) in items {
+            let overflow = inventory.add(&self.registry, *item, *amount);
+            if overflow > 0 {
+                self.drop_event.send(DropItemEvent {
+                    actor,
+                    stack: self.registry.new_stack(*item, overflow),
+                });
+            }
+        }
+    }
+
+    fn take(&mut self, items: &[(ItemId, u32)]) -> bool {
+        let Ok((_, _, mut inventory)) = self.player.get_single_mut() else {
+            return false;
+        };
+
+        let mut totals: Vec<(ItemId, u32)> = Vec::new();
+        for (item, amount) in items {
+            match totals.iter_mut().find(|(total_item, _)| total_item == item) {
+                Some((_, total)) => *total += amount,
+                None => totals.push((*item, *amount)),
+            }
+        }
+        if !totals
+            .iter()
+            .all(|(item, amount)| inventory.has(*item, *amount))
+        {
+            return false;
+        }
+
+        for (item, amount) in &totals {
+            inventory.remove(*item, *amount);
+        }
+        true
+    }
+}
+
+fn record_builds(
+    mut build_events: EventReader<BuildEvent>,
+    mut history: ResMut<BuildHistory>,
+    time: Res<Time>,
+) {
+    for build_event in build_events.read() {
+        let pieces = build_event
+            .placed
+            .iter()
+            .map(|(entity, transform)| HistoryPiece {
+                entity: *entity,
+                kind: build_event.kind,
+                transform: *transform,
+                site_progress: Some(0.0),
+            })
+            .collect();
+
+        history.push(BuildOperation::Build {
+            pieces,
+            placed_at: time.elapsed_seconds(),
+        });
+    }
+}
+
+fn record_deconstructions(
+    mut deconstruct_events: EventReader<DeconstructEvent>,
+    mut history: ResMut<BuildHistory>,
+    player: Query<(), With<Player>>,
+    buildings: Query<(&Building, &Transform, Option<&ConstructionSite>)>,
+    refund: Res<DeconstructRefund>,
+) {
+    for deconstruct_event in deconstruct_events.read() {
+        if !player.contains(deconstruct_event.actor) {
+            continue;
+        }
+        let Ok((building, transform, site)) = buildings.get(deconstruct_event.building) else {
+            continue;
+        };
+
+        history.push(BuildOperation::Deconstruct {
+            piece: HistoryPiece {
+                entity: deconstruct_event.building,
+                kind: building.kind,
+                transform: *transform,
+                site_progress: site.map(|site| site.progress),
+            },
+            refund: refund.refund(&building.kind.cost()),
+        });
+    }
+}
+
+fn undo(
+    input: InputParam,
+    mut history: ResMut<BuildHistory>,
+    mut operations: BuildOperations,
+    refund: Res<DeconstructRefund>,
+    time: Res<Time>,
+) {
+    if !input.action_just_pressed(PlayerAction::Undo) {
+        return;
+    }
+
+    let Some(mut operation) = history.undo.pop() else {
+        info!("Nothing to undo");
+        return;
+    };
+
+    match &mut operation {
+        BuildOperation::Build { pieces, placed_at } => {
+            let cost = operations.despawn(pieces);
+            // Mistakes noticed quickly cost nothing
+            if time.elapsed_seconds() - *placed_at <= UNDO_REFUND_WINDOW {
+                operations.give(&cost);
+            } else {
+                operations.give(&refund.refund(&cost));
+            }
+        }
+        BuildOperation::Deconstruct { piece, refund } => {
+            if !operations.can_place(&[*piece]) {
+                info!(
+                    "Cannot restore the {}, something is in the way",
+                    piece.kind.name()
+                );
+                history.undo.push(operation);
+                return;
+            }
+            if !operations.take(refund) {
+                info!("Not enough resources to restore the {}", piece.kind.name());
+                history.undo.push(operation);
+                return;
+            }
+
+            let entity = operations.restore(piece);
+            history.replace_entity(piece.entity, entity);
+            piece.entity = entity;
+        }
+    }
+
+    history.redo.push(operation);
+}
+
+fn redo(
+    input: InputParam,
+    mut history: ResMut<BuildHistory>,
+    mut operations: BuildOperations,
+    time: Res<Time>,
+) {
+    if !input.action_just_pressed(PlayerAction::Redo) {
+        return;
+    }
+
+    let Some(mut operation) = history.redo.pop() else {
+        info!("Nothing to redo");
+        return;
+    };
+
+    match &mut operation {
+        BuildOperation::Build { pieces, placed_at } => {
+            let cost: Vec<_> = pieces.iter().flat_map(|piece| piece.kind.cost()).collect();
+            if !operations.can_place(pieces) {
+                info!("Cannot rebuild, something is in the way");
+                history.redo.push(operation);
+                return;
+            }
+            if !operations.take(&cost) {
+                info!("Not enough resources to rebuild");
+                history.redo.push(operation);
+                return;
+            }
+
+            for piece in pieces.iter_mut() {
+                let entity = operations.rebuild(piece);
+                history.replace_entity(piece.entity, entity);
+                piece.entity = entity;
+            }
+            *placed_at = time.elapsed_seconds();
+        }
+        BuildOperation::Deconstruct { piece, refund } => {
+            if !operations.despawn(&[*piece]).is_empty() {
+                operations.give(refund);
+            }
+        }
+    }
+
+    history.undo.push(operation);
+}
@@ -0,0 +1,257 @@
+use bevy::{color::palettes, prelude::*};
+
+use crate::{
+    build::{BuildingPreview, DragPlacement, PreviewPlacement, SelectedBuilding},
+    building::BuildingKind,
+    inventory::Inventory,
+    item::ItemRegistry,
+    player::{Player, PlayerState},
+    player_input::{InputMap, PlayerAction},
+};
+
+const SELECTED_COLOR: Color = Color::srgb(0.3, 0.45, 0.3);
+const BUTTON_COLOR: Color = Color::srgb(0.25, 0.25, 0.25);
+const HOVERED_COLOR: Color = Color::srgb(0.32, 0.32, 0.32);
+
+pub struct BuildMenuPlugin;
+
+impl Plugin for BuildMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(PlayerState::BuildingMode), spawn_build_menu)
+            .add_systems(OnExit(PlayerState::BuildingMode), despawn_build_menu)
+            .add_systems(
+                Update,
+                (
+                    press_building_buttons,
+                    update_building_buttons,
+                    update_placement_text,
+                )
+                    .chain()
+                    .run_if(in_state(PlayerState::BuildingMode)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct BuildMenu;
+
+#[derive(Component)]
+struct BuildingButton(usize);
+
+#[derive(Component)]
+struct CostText;
+
+#[derive(Component)]
+struct PlacementText;
+
+fn text_style(font_size: f32) -> TextStyle {
+    TextStyle {
+        font_size,
+        color: palettes::basic::WHITE.into(),
+        ..Default::default()
+    }
+}
+
+fn spawn_build_menu(mut commands: Commands, registry: Res<ItemRegistry>, input_map: Res<InputMap>) {
+    let hint = format!(
+        "[{}] next  [{}] rotate  [{}] place  [Drag] place many\n[{}] undo  [{}] redo  [{}] cancel",
+        input_map.key_label(PlayerAction::NextBuilding),
+        input_map.key_label(PlayerAction::RotateBuilding),
+        input_map.key_label(PlayerAction::Build),
+        input_map.key_label(PlayerAction::Undo),
+        input_map.key_label(PlayerAction::Redo),
+        input_map.key_label(PlayerAction::Cancel),
+    );
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(40.0),
+                left: Val::Px(8.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(8.0)),
+                row_gap: Val::Px(4.0),
+                width: Val::Px(220.0),
+                ..Default::default()
+            },
+            background_color: Color::srgba(0.15, 0.15, 0.15, 0.9).into(),
+            ..Default::default()
+        })
+        // Tracks hovering so clicks on the menu do not place buildings
+        .insert((BuildMenu, Interaction::default()))
+        .with_children(|builder| {
+            builder.spawn(TextBundle::from_section("Build", text_style(24.0)));
+
+            for (index, kind) in BuildingKind::ALL.iter().enumerate() {
+                let cost: Vec<_> = kind
+                    .cost()
+                    .iter()
+                    .map(|(item, amount)| format!("{} {}", amount, registry.name(*item)))
+                    .collect();
+
+                builder
+                    .spawn(ButtonBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            padding: UiRect::all(Val::Px(4.0)),
+                            ..Default::default()
+                        },
+                        background_color: BUTTON_COLOR.into(),
+                        ..Default::default()
+                    })
+                    .insert(BuildingButton(index))
+                    .with_children(|builder| {
+                        builder.spawn(TextBundle::from_section(kind.name(), text_style(18.0)));
+                        builder.spawn((
+                            TextBundle::from_section(cost.join(", "), text_style(14.0)),
+                            CostText,
+                        ));
+                    });
+            }
+
+            builder.spawn((
+                TextBundle::from_section("", text_style(14.0)).with_style(Style {
+                    display: Display::None,
+                    ..Default::default()
+                }),
+                PlacementText,
+            ));
+
+            builder.spawn(TextBundle::from_section(hint, text_style(14.0)));
+        });
+}
+
+fn despawn_build_menu(mut commands: Commands, menus: Query<Entity, With<BuildMenu>>) {
+    for menu in &menus {
+        commands.entity(menu).despawn_recursive();
+    }
+}
+
+fn press_building_buttons(
+    buttons: Query<(&Interaction, &BuildingButton), Changed<Interaction>>,
+    mut selected_building: ResMut<SelectedBuilding>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction == Interaction::Pressed && selected_building.0 != button.0 {
+            selected_building.0 = button.0;
+        }
+    }
+}
+
+fn update_building_buttons(
+    mut buttons: Query<(
+        &BuildingButton,
+        &Interaction,
+        &mut BackgroundColor,
+        &Children,
+    )>,
+    mut cost_texts: Query<&mut Text, With<CostText>>,
+    player: Query<&Inventory, With<Player>>,
+    selected_building: Res<SelectedBuilding>,
+) {
+    let Ok(inventory) = player.get_single() else {
+        return;
+    };
+
+    for (button, interaction, mut background_color, children) in &mut buttons {
+        let color = if button.0 == selected_building.0 {
+            SELECTED_COLOR
+        } else if *interaction == Interaction::Hovered {
+            HOVERED_COLOR
+        } else {
+            BUTTON_COLOR
+        };
+        if background_color.0 != color {
+            background_color.0 = color;
+        }
+
+        // Costs the player cannot pay are shown in red
+        let affordable = BuildingKind::ALL[button.0]
+            .cost()
+            .iter()
+            .all(|(item, amount)| inventory.has(*item, *amount));
+        let text_color: Color = if affordable {
+            palettes::basic::WHITE.into()
+        } else {
+            palettes::css::TOMATO.into()
+        };
+
+        for child in children {
+            if let Ok(mut cost_text) = cost_texts.get_mut(*child) {
+                if cost_text.sections[0].style.color != text_color {
+                    cost_text.sections[0].style.color = text_color;
+                }
+            }
+        }
+    }
+}
+
+// Shows the total cost while dragging, otherwise why the cursor preview
+// cannot be placed
+fn update_placement_text(
+    mut placement_text: Query<(&mut Text, &mut Style), With<PlacementText>>,
+    preview: Query<&PreviewPlacement, With<BuildingPreview>>,
+    player: Query<&Inventory, With<Player>>,
+    drag: Res<DragPlacement>,
+    selected_building: Res<SelectedBuilding>,
+    registry: Res<ItemRegistry>,
+) {
+    let Ok((mut text, mut style)) = placement_text.get_single_mut() else {
+        return;
+    };
+    let Ok(inventory) = player.get_single() else {
+        return;
+    };
+
+    let (value, affordable) = if drag.is_dragging() {
+        let cost: Vec<_> = selected_building
+            .kind()
+            .cost()
+            .into_iter()
+            .map(|(item, amount)| (item, amount * drag.valid as u32))
+            .collect();
+        let affordable = cost
+            .iter()
+            .all(|(item, amount)| inventory.has(*item, *amount));
+        let cost: Vec<_> = cost
+            .iter()
+            .map(|(item, amount)| format!("{} {}", amount, registry.name(*item)))
+            .collect();
+
+        let mut value = format!("{} pieces: {}", drag.valid, cost.join(", "));
+        let blocked = drag.positions.len() - drag.valid;
+        if blocked > 0 {
+            value += &format!(" ({} blocked)", blocked);
+        }
+
+        (Some(value), affordable)
+    } else {
+        let error = preview
+            .get_single()
+            .ok()
+            .and_then(|placement| placement.error);
+        (error.map(|error| error.reason().to_string()), false)
+    };
+
+    let Some(value) = value else {
+        if style.display != Display::None {
+            style.display = Display::None;
+        }
+        return;
+    };
+
+    let color: Color = if affordable {
+        palettes::basic::WHITE.into()
+    } else {
+        palettes::css::TOMATO.into()
+    };
+
+    if style.display != Display::Flex {
+        style.display = Display::Flex;
+    }
+    if text.sections[0].value != value || text.sections[0].style.color != color {
+        text.sections[0].value = value;
+        text.sections[0].style.color = color;
+    }
+}
@@ -0,0 +1,515 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{
+    color::palettes,
+    ecs::{
+        component::{ComponentHooks, StorageType},
+        system::EntityCommands,
+    },
+    prelude::*,
+    utils::HashMap,
+};
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    building_connection::{BuildingConnectionPlugin, Connections},
+    building_grid::{
+        footprint_cells, quarter_turns, rotated_footprint, BuildingGrid, BuildingGridPlugin,
+    },
+    construction::ConstructionPlugin,
+    crafting::Workstation,
+    defense::{DefensePlugin, Gate, SpikeTrap, Turret},
+    health::Health,
+    interaction::{Interactable, InteractionEvent, InteractionKind},
+    inventory::Inventory,
+    item::ItemId,
+    storage::{Chest, CHEST_SIZE},
+    tool::{EquippedTool, ToolKind, ToolUsedEvent},
+};
+
+const REPAIR_DURATION: f32 = 0.5;
+const SCAFFOLD_COLOR: Color = Color::srgba(0.85, 0.75, 0.5, 0.35);
+// Share of max health restored by each repair, paid with the same share of the cost
+const REPAIR_FRACTION: f32 = 0.25;
+
+pub struct BuildingPlugin;
+
+impl Plugin for BuildingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            BuildingGridPlugin,
+            BuildingConnectionPlugin,
+            ConstructionPlugin,
+            DefensePlugin,
+        ))
+        .add_systems(Startup, setup)
+        .add_systems(
+            Update,
+            (
+                toggle_doors.run_if(on_event::<InteractionEvent>()),
+                repair_buildings.run_if(on_event::<InteractionEvent>()),
+                destroy_buildings,
+            ),
+        );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum BuildingLayer {
+    Floor,
+    Structure,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum BuildingKind {
+    Wall,
+    Floor,
+    Door,
+    Fence,
+    Campfire,
+    Chest,
+    Workbench,
+    Turret,
+    SpikeTrap,
+    Gate,
+}
+
+impl BuildingKind {
+    pub const ALL: [BuildingKind; 10] = [
+        BuildingKind::Wall,
+        BuildingKind::Floor,
+        BuildingKind::Door,
+        BuildingKind::Fence,
+        BuildingKind::Campfire,
+        BuildingKind::Chest,
+        BuildingKind::Workbench,
+        BuildingKind::Turret,
+        BuildingKind::SpikeTrap,
+        BuildingKind::Gate,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuildingKind::Wall => "Wall",
+            BuildingKind::Floor => "Floor",
+            BuildingKind::Door => "Door",
+            BuildingKind::Fence => "Fence",
+            BuildingKind::Campfire => "Campfire",
+            BuildingKind::Chest => "Chest",
+            BuildingKind::Workbench => "Workbench",
+            BuildingKind::Turret => "Turret",
+            BuildingKind::SpikeTrap => "Spike trap",
+            BuildingKind::Gate => "Gate",
+        }
+    }
+
+    pub fn cost(&self) -> Vec<(ItemId, u32)> {
+        match self {
+            BuildingKind::Wall => vec![(ItemId::Plank, 4)],
+            BuildingKind::Floor => vec![(ItemId::Plank, 2)],
+            BuildingKind::Door => vec![(ItemId::Plank, 4), (ItemId::Rope, 1)],
+            BuildingKind::Fence => vec![(ItemId::Wood, 2)],
+            BuildingKind::Campfire => vec![(ItemId::Wood, 6), (ItemId::Stone, 8)],
+            BuildingKind::Chest => vec![(ItemId::Plank, 8)],
+            BuildingKind::Workbench => vec![(ItemId::Plank, 10), (ItemId::Stone, 4)],
+            BuildingKind::Turret => {
+                vec![(ItemId::Plank, 10), (ItemId::Stone, 10), (ItemId::Ingot, 2)]
+            }
+            BuildingKind::SpikeTrap => vec![(ItemId::Plank, 2), (ItemId::Ingot, 1)],
+            BuildingKind::Gate => vec![(ItemId::Plank, 6), (ItemId::Ingot, 1)],
+        }
+    }
+
+    // Cells covered along x and z before rotation
+    pub fn footprint(&self) -> UVec2 {
+        match self {
+            BuildingKind::Workbench => UVec2::new(2, 1),
+            _ => UVec2::ONE,
+        }
+    }
+
+    pub fn layer(&self) -> BuildingLayer {
+        match self {
+            BuildingKind::Floor => BuildingLayer::Floor,
+            _ => BuildingLayer::Structure,
+        }
+    }
+
+    pub fn size(&self) -> Vec3 {
+        match self {
+            BuildingKind::Wall => Vec3::new(1.0, 2.0, 0.2),
+            BuildingKind::Floor => Vec3::new(1.0, 0.1, 1.0),
+            BuildingKind::Door => Vec3::new(1.0, 2.0, 0.15),
+            BuildingKind::Fence => Vec3::new(1.0, 0.9, 0.1),
+            BuildingKind::Campfire => Vec3::new(0.8, 0.3, 0.8),
+            BuildingKind::Chest => Vec3::new(0.9, 0.6, 0.6),
+            BuildingKind::Workbench => Vec3::new(1.9, 0.8, 0.9),
+            BuildingKind::Turret => Vec3::new(0.9, 2.0, 0.9),
+            BuildingKind::SpikeTrap => Vec3::new(0.9, 0.15, 0.9),
+            BuildingKind::Gate => Vec3::new(1.0, 2.0, 0.25),
+        }
+    }
+
+    fn mesh(&self) -> Mesh {
+        let size = self.size();
+        match self {
+            BuildingKind::Campfire | BuildingKind::Turret => {
+                Cylinder::new(size.x / 2.0, size.y).into()
+            }
+            _ => Cuboid::from_size(size).into(),
+        }
+    }
+
+    pub fn collider(&self) -> Collider {
+        let half_size = self.size() / 2.0;
+        match self {
+            BuildingKind::Campfire | BuildingKind::Turret => {
+                Collider::cylinder(half_size.y, half_size.x)
+            }
+            _ => Collider::cuboid(half_size.x, half_size.y, half_size.z),
+        }
+    }
+
+    // Seconds of work needed to finish a construction site
+    pub fn build_time(&self) -> f32 {
+        match self {
+            BuildingKind::Floor | BuildingKind::Fence => 1.5,
+            BuildingKind::Campfire | BuildingKind::SpikeTrap => 2.0,
+            BuildingKind::Chest => 2.5,
+            BuildingKind::Wall | BuildingKind::Door => 3.0,
+            BuildingKind::Gate => 4.0,
+            BuildingKind::Workbench => 5.0,
+            BuildingKind::Turret => 8.0,
+        }
+    }
+
+    // Walls and fences join up with their neighbours
+    pub fn is_connectable(&self) -> bool {
+        matches!(self, BuildingKind::Wall | BuildingKind::Fence)
+    }
+
+    pub fn connects_to(&self, other: BuildingKind) -> bool {
+        match self {
+            BuildingKind::Wall => matches!(
+                other,
+                BuildingKind::Wall | BuildingKind::Door | BuildingKind::Gate
+            ),
+            BuildingKind::Fence => other == BuildingKind::Fence,
+            _ => false,
+        }
+    }
+
+    // Floors and traps are walked on, so they do not block movement
+    fn is_solid(&self) -> bool {
+        self.layer() != BuildingLayer::Floor && *self != BuildingKind::SpikeTrap
+    }
+
+    fn color(&self) -> Srgba {
+        match self {
+            BuildingKind::Wall => palettes::css::BURLYWOOD,
+            BuildingKind::Floor => palettes::css::TAN,
+            BuildingKind::Door => palettes::css::SIENNA,
+            BuildingKind::Fence => palettes::css::PERU,
+            BuildingKind::Campfire => palettes::css::ORANGE_RED,
+            BuildingKind::Chest => palettes::css::SADDLE_BROWN,
+            BuildingKind::Workbench => palettes::css::CHOCOLATE,
+            BuildingKind::Turret => palettes::basic::GRAY,
+            BuildingKind::SpikeTrap => palettes::css::DARK_SLATE_GRAY,
+            BuildingKind::Gate => palettes::css::DARK_GOLDENROD,
+        }
+    }
+
+    fn health(&self) -> i32 {
+        match self {
+            BuildingKind::Wall => 80,
+            BuildingKind::Floor | BuildingKind::Fence | BuildingKind::SpikeTrap => 30,
+            BuildingKind::Door => 50,
+            BuildingKind::Campfire => 25,
+            BuildingKind::Chest | BuildingKind::Workbench => 40,
+            BuildingKind::Turret => 60,
+            BuildingKind::Gate => 100,
+        }
+    }
+
+    fn workstation(&self) -> Option<Workstation> {
+        match self {
+            BuildingKind::Workbench => Some(Workstation::Workbench),
+            BuildingKind::Campfire => Some(Workstation::Campfire),
+            _ => None,
+        }
+    }
+}
+
+pub struct Building {
+    pub kind: BuildingKind,
+    pub cells: Vec<IVec2>,
+    pub quarter_turns: u32,
+}
+
+impl Building {
+    pub fn new(kind: BuildingKind, transform: Transform) -> Self {
+        let footprint = rotated_footprint(kind.footprint(), transform.rotation);
+        Self {
+            kind,
+            cells: footprint_cells(footprint, transform.translation),
+            quarter_turns: quarter_turns(transform.rotation),
+        }
+    }
+}
+
+impl Component for Building {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    // Keeps the building grid in sync however buildings are spawned or despawned
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks
+            .on_add(|mut world, entity, _| {
+                let Some(building) = world.get::<Building>(entity) else {
+                    return;
+                };
+                let (cells, layer) = (building.cells.clone(), building.kind.layer());
+
+                world
+                    .resource_mut::<BuildingGrid>()
+                    .insert(&cells, layer, entity);
+            })
+            .on_remove(|mut world, entity, _| {
+                let Some(building) = world.get::<Building>(entity) else {
+                    return;
+                };
+                let (cells, layer) = (building.cells.clone(), building.kind.layer());
+
+                world
+                    .resource_mut::<BuildingGrid>()
+                    .remove(&cells, layer, entity);
+            });
+    }
+}
+
+#[derive(Component)]
+pub struct Door {
+    pub open: bool,
+}
+
+#[derive(Resource)]
+pub struct BuildingAssets {
+    meshes: HashMap<BuildingKind, Handle<Mesh>>,
+    materials: HashMap<BuildingKind, Handle<StandardMaterial>>,
+    scaffold_material: Handle<StandardMaterial>,
+}
+
+impl BuildingAssets {
+    pub fn mesh(&self, kind: BuildingKind) -> Handle<Mesh> {
+        self.meshes[&kind].clone()
+    }
+
+    pub fn material(&self, kind: BuildingKind) -> Handle<StandardMaterial> {
+        self.materials[&kind].clone()
+    }
+
+    pub fn scaffold_material(&self) -> Handle<StandardMaterial> {
+        self.scaffold_material.clone()
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let building_assets = BuildingAssets {
+        meshes: BuildingKind::ALL
+            .iter()
+            .map(|kind| (*kind, meshes.add(kind.mesh())))
+            .collect(),
+        materials: BuildingKind::ALL
+            .iter()
+            .map(|kind| {
+                let material = StandardMaterial::from_color(kind.color());
+                (*kind, materials.add(material))
+            })
+            .collect(),
+        scaffold_material: materials.add(StandardMaterial::from_color(SCAFFOLD_COLOR)),
+    };
+
+    commands.insert_resource(building_assets);
+}
+
+pub fn spawn_building(
+    commands: &mut Commands,
+    building_assets: &BuildingAssets,
+    kind: BuildingKind,
+    transform: Transform,
+) -> Entity {
+    let mut building = commands.spawn(PbrBundle {
+        mesh: building_assets.mesh(kind),
+        material: building_assets.material(kind),
+        transform,
+        ..Default::default()
+    });
+
+    building.insert((
+        RigidBody::Fixed,
+        kind.collider(),
+        Building::new(kind, transform),
+        Name::new(kind.name()),
+    ));
+    finish_building(&mut building, kind);
+
+    building.id()
+}
+
+// Adds what makes a building usable, whether spawned outright or completed
+// from a construction site
+pub fn finish_building(building: &mut EntityCommands, kind: BuildingKind) {
+    building.insert(Health::new_full(kind.health()));
+
+    if !kind.is_solid() {
+        building.insert(Sensor);
+    }
+
+    if kind.is_connectable() {
+        building.insert(Connections::default());
+    }
+
+    if let Some(workstation) = kind.workstation() {
+        building.insert(workstation);
+    }
+
+    match kind {
+        BuildingKind::Turret => {
+            building.insert(Turret::default());
+        }
+        BuildingKind::SpikeTrap => {
+            building.insert(SpikeTrap::default());
+        }
+        BuildingKind::Gate => {
+            building.insert(Gate::default());
+        }
+        _ => {}
+    }
+
+    match kind {
+        BuildingKind::Chest => {
+            building.insert((
+                Chest,
+                Inventory::new(CHEST_SIZE),
+                Interactable::new(InteractionKind::Open, "Open chest")
+                    .with_kind(InteractionKind::Repair),
+            ));
+        }
+        BuildingKind::Door => {
+            building.insert((
+                Door { open: false },
+                Interactable::new(InteractionKind::Open, "Open door")
+                    .with_kind(InteractionKind::Repair),
+            ));
+        }
+        _ => {
+            building.insert(
+                Interactable::new(InteractionKind::Repair, format!("Repair {}", kind.name()))
+                    .with_duration(REPAIR_DURATION),
+            );
+        }
+    }
+}
+
+// Doors swing 90° around their hinge, clearing the doorway
+fn toggle_doors(
+    mut doors: Query<(&mut Door, &mut Interactable, &mut Transform)>,
+    mut interaction_events: EventReader<InteractionEvent>,
+) {
+    let hinge = Vec3::new(-BuildingKind::Door.size().x / 2.0, 0.0, 0.0);
+    let rotation = Quat::from_rotation_y(FRAC_PI_2);
+    let swing = Transform::from_translation(hinge - rotation * hinge).with_rotation(rotation);
+
+    for interaction_event in interaction_events.read() {
+        if interaction_event.kind != InteractionKind::Open {
+            continue;
+        }
+
+        let Ok((mut door, mut interactable, mut transform)) =
+            doors.get_mut(interaction_event.target)
+        else {
+            continue;
+        };
+
+        door.open = !door.open;
+
+        if door.open {
+            *transform = transform.mul_transform(swing);
+            interactable.prompt = "Close door".to_string();
+        } else {
+            *transform =
+                transform.mul_transform(Transform::from_matrix(swing.compute_matrix().inverse()));
+            interactable.prompt = "Open door".to_string();
+        }
+    }
+}
+
+fn repair_buildings(
+    mut actors: Query<(&mut Inventory, &EquippedTool)>,
+    mut buildings: Query<(&Building, &mut Health)>,
+    mut interaction_events: EventReader<InteractionEvent>,
+    mut tool_used_event: EventWriter<ToolUsedEvent>,
+) {
+    for interaction_event in interaction_events.read() {
+        if interaction_event.kind != InteractionKind::Repair {
+            continue;
+        }
+
+        let Ok((building, mut health)) = buildings.get_mut(interaction_event.target) else {
+            continue;
+        };
+        let Ok((mut inventory, equipped_tool)) = actors.get_mut(interaction_event.actor) else {
+            continue;
+        };
+
+        if equipped_tool.kind() != Some(ToolKind::Hammer) {
+            info!("A hammer is needed to repair the {}", building.kind.name());
+            continue;
+        }
+
+        let missing = health.max() - health.current();
+        if missing <= 0 {
+            continue;
+        }
+
+        let restored = missing.min((health.max() as f32 * REPAIR_FRACTION).ceil() as i32);
+        let share = restored as f32 / health.max() as f32;
+        let cost: Vec<_> = building
+            .kind
+            .cost()
+            .into_iter()
+            .map(|(item, amount)| (item, (amount as f32 * share).ceil() as u32))
+            .collect();
+
+        if !cost
+            .iter()
+            .all(|(item, amount)| inventory.has(*item, *amount))
+        {
+            info!(
+                "Not enough resources to repair the {}",
+                building.kind.name()
+            );
+            continue;
+        }
+        for (item, amount) in &cost {
+            inventory.remove(*item, *amount);
+        }
+
+        health.change(restored);
+        tool_used_event.send(ToolUsedEvent {
+            actor: interaction_event.actor,
+        });
+    }
+}
+
+fn destroy_buildings(mut commands: Commands, buildings: Query<(Entity, &Health), With<Building>>) {
+    for (entity, health) in &buildings {
+        if health.is_dead() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use bevy::{prelude::*, utils::HashMap};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    build::CELL_SIZE,
+    building::{Building, BuildingKind, BuildingLayer},
+    building_grid::BuildingGrid,
+    construction::ConstructionSite,
+};
+
+// Local directions matching the bits of `Connections`
+const DIRECTIONS: [Vec3; 4] = [Vec3::X, Vec3::NEG_X, Vec3::Z, Vec3::NEG_Z];
+
+pub struct BuildingConnectionPlugin;
+
+impl Plugin for BuildingConnectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup).add_systems(
+            PostUpdate,
+            update_connections.run_if(resource_changed::<BuildingGrid>),
+        );
+    }
+}
+
+// Bitmask of the neighbours a piece joins, in its local space. Depending on
+// the bits set a piece is drawn as an end, straight, corner, T or cross.
+#[derive(Component, Default)]
+pub struct Connections(pub u8);
+
+#[derive(Resource)]
+struct ConnectionMeshes {
+    meshes: HashMap<(BuildingKind, u8), Handle<Mesh>>,
+}
+
+// Boxes making up a piece as (offset, size). Unconnected pieces keep their
+// plain shape, others are a post with an arm towards each neighbour.
+fn connection_shapes(kind: BuildingKind, connections: u8) -> Vec<(Vec3, Vec3)> {
+    let size = kind.size();
+    if connections == 0 {
+        return vec![(Vec3::ZERO, size)];
+    }
+
+    let thickness = size.z;
+    let mut shapes = vec![(Vec3::ZERO, Vec3::new(thickness, size.y, thickness))];
+
+    for (bit, direction) in DIRECTIONS.iter().enumerate() {
+        if connections & (1 << bit) == 0 {
+            continue;
+        }
+
+        let arm_size = if direction.x != 0.0 {
+            Vec3::new(CELL_SIZE / 2.0, size.y, thickness)
+        } else {
+            Vec3::new(thickness, size.y, CELL_SIZE / 2.0)
+        };
+        shapes.push((*direction * CELL_SIZE / 4.0, arm_size));
+    }
+
+    shapes
+}
+
+fn connection_mesh(kind: BuildingKind, connections: u8) -> Mesh {
+    let mut shapes = connection_shapes(kind, connections).into_iter();
+    let (offset, size) = shapes.next().unwrap();
+    let mut mesh = Mesh::from(Cuboid::from_size(size)).translated_by(offset);
+
+    for (offset, size) in shapes {
+        mesh.merge(&Mesh::from(Cuboid::from_size(size)).translated_by(offset));
+    }
+
+    mesh
+}
+
+fn connection_collider(kind: BuildingKind, connections: u8) -> Collider {
+    let shapes = connection_shapes(kind, connections)
+        .into_iter()
+        .map(|(offset, size)| {
+            let half_size = size / 2.0;
+            (
+                offset,
+                Quat::IDENTITY,
+                Collider::cuboid(half_size.x, half_size.y, half_size.z),
+            )
+        })
+        .collect();
+
+    Collider::compound(shapes)
+}
+
+fn setup(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    let connection_meshes = ConnectionMeshes {
+        meshes: BuildingKind::ALL
+            .iter()
+            .filter(|kind| kind.is_connectable())
+            .flat_map(|kind| (0..16).map(move |connections| (*kind, connections)))
+            .map(|(kind, connections)| {
+                let mesh = meshes.add(connection_mesh(kind, connections));
+                ((kind, connections), mesh)
+            })
+            .collect(),
+    };
+
+    commands.insert_resource(connection_meshes);
+}
+
+// Re-evaluates every piece whenever the grid changes, only touching the ones
+// whose neighbours differ. Construction sites are left out so pieces only
+// join finished structures
+fn update_connections(
+    mut commands: Commands,
+    mut pieces: Query<(Entity, &Building, &Transform, &mut Connections)>,
+    buildings: Query<&Building, Without<ConstructionSite>>,
+    building_grid: Res<BuildingGrid>,
+    connection_meshes: Res<ConnectionMeshes>,
+) {
+    for (entity, building, transform, mut connections) in &mut pieces {
+        let Some(cell) = building.cells.first() else {
+            continue;
+        };
+
+        let mut mask = 0;
+        for (bit, direction) in DIRECTIONS.iter().enumerate() {
+            let world_direction = (transform.rotation * *direction).round();
+            let neighbour = *cell + IVec2::new(world_direction.x as i32, world_direction.z as i32);
+
+            let connected = building_grid
+                .get(neighbour, BuildingLayer::Structure)
+                .and_then(|neighbour| buildings.get(neighbour).ok())
+                .is_some_and(|neighbour| building.kind.connects_to(neighbour.kind));
+
+            if connected {
+                mask |= 1 << bit;
+            }
+        }
+
+        if connections.0 == mask {
+            continue;
+        }
+        connections.0 = mask;
+
+        commands.entity(entity).insert((
+            connection_meshes.meshes[&(building.kind, mask)].clone(),
+            connection_collider(building.kind, mask),
+        ));
+    }
+}
@@ -0,0 +1,162 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{build::CELL_SIZE, building::BuildingLayer};
+
+const NEIGHBOUR_OFFSETS: [IVec2; 4] = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y];
+
+pub struct BuildingGridPlugin;
+
+impl Plugin for BuildingGridPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BuildingGrid>();
+    }
+}
+
+// Cells are indexed by (x, z) on the ground plane. Floors and structures are
+// stored in separate layers so walls can stand on floors.
+#[derive(Resource, Default)]
+pub struct BuildingGrid {
+    cells: HashMap<(IVec2, BuildingLayer), Entity>,
+}
+
+impl BuildingGrid {
+    pub fn get(&self, cell: IVec2, layer: BuildingLayer) -> Option<Entity> {
+        self.cells.get(&(cell, layer)).copied()
+    }
+
+    pub fn is_free(&self, cells: &[IVec2], layer: BuildingLayer) -> bool {
+        cells
+            .iter()
+            .all(|cell| !self.cells.contains_key(&(*cell, layer)))
+    }
+
+    // Only structures get in the way, floors can be walked over
+    pub fn is_walkable(&self, cell: IVec2) -> bool {
+        !self.cells.contains_key(&(cell, BuildingLayer::Structure))
+    }
+
+    pub fn neighbours(
+        &self,
+        cell: IVec2,
+        layer: BuildingLayer,
+    ) -> impl Iterator<Item = (IVec2, Entity)> + '_ {
+        NEIGHBOUR_OFFSETS.iter().filter_map(move |offset| {
+            let neighbour = cell + *offset;
+            self.get(neighbour, layer).map(|entity| (neighbour, entity))
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (IVec2, BuildingLayer, Entity)> + '_ {
+        self.cells
+            .iter()
+            .map(|((cell, layer), entity)| (*cell, *layer, *entity))
+    }
+
+    pub fn insert(&mut self, cells: &[IVec2], layer: BuildingLayer, entity: Entity) {
+        for cell in cells {
+            self.cells.insert((*cell, layer), entity);
+        }
+    }
+
+    // Leaves cells alone that were taken over by another entity
+    pub fn remove(&mut self, cells: &[IVec2], layer: BuildingLayer, entity: Entity) {
+        for cell in cells {
+            if self.get(*cell, layer) == Some(entity) {
+                self.cells.remove(&(*cell, layer));
+            }
+        }
+    }
+}
+
+pub fn world_to_cell(position: Vec3) -> IVec2 {
+    (position.xz() / CELL_SIZE).floor().as_ivec2()
+}
+
+pub fn cell_to_world(cell: IVec2) -> Vec3 {
+    let center = (cell.as_vec2() + 0.5) * CELL_SIZE;
+    Vec3::new(center.x, 0.0, center.y)
+}
+
+// Rotation around y rounded to whole quarter turns
+pub fn quarter_turns(rotation: Quat) -> u32 {
+    let (yaw, _, _) = rotation.to_euler(EulerRot::YXZ);
+    ((yaw / FRAC_PI_2).round() as i32).rem_euclid(4) as u32
+}
+
+// Footprint along x and z once turned by `rotation`
+pub fn rotated_footprint(footprint: UVec2, rotation: Quat) -> UVec2 {
+    let rotated = rotation * Vec3::new(footprint.x as f32, 0.0, footprint.y as f32);
+    UVec2::new(
+        rotated.x.abs().round() as u32,
+        rotated.z.abs().round() as u32,
+    )
+}
+
+// Cells covered by a footprint centred on `center`
+pub fn footprint_cells(footprint: UVec2, center: Vec3) -> Vec<IVec2> {
+    let corner = center.xz() - footprint.as_vec2() * CELL_SIZE / 2.0;
+    let first = (corner / CELL_SIZE + 0.5).floor().as_ivec2();
+
+    (0..footprint.x as i32)
+        .flat_map(|x| (0..footprint.y as i32).map(move |z| first + IVec2::new(x, z)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+
+    #[test]
+    fn quarter_turns_swap_footprint_axes() {
+        let footprint = UVec2::new(2, 1);
+
+        assert_eq!(rotated_footprint(footprint, Quat::IDENTITY), footprint);
+        assert_eq!(
+            rotated_footprint(footprint, Quat::from_rotation_y(FRAC_PI_2)),
+            UVec2::new(1, 2)
+        );
+        assert_eq!(
+            rotated_footprint(footprint, Quat::from_rotation_y(-FRAC_PI_2)),
+            UVec2::new(1, 2)
+        );
+        assert_eq!(
+            rotated_footprint(footprint, Quat::from_rotation_y(PI)),
+            footprint
+        );
+    }
+
+    #[test]
+    fn quarter_turns_round_to_whole_turns() {
+        assert_eq!(quarter_turns(Quat::IDENTITY), 0);
+        assert_eq!(quarter_turns(Quat::from_rotation_y(FRAC_PI_2 + 0.1)), 1);
+        assert_eq!(quarter_turns(Quat::from_rotation_y(-FRAC_PI_2)), 3);
+    }
+
+    #[test]
+    fn footprint_cells_cover_multi_cell_pieces() {
+        let footprint = UVec2::new(2, 1);
+
+        assert_eq!(
+            footprint_cells(footprint, Vec3::new(1.0, 0.0, 0.5)),
+            vec![IVec2::new(0, 0), IVec2::new(1, 0)]
+        );
+
+        let turned = rotated_footprint(footprint, Quat::from_rotation_y(FRAC_PI_2));
+        assert_eq!(
+            footprint_cells(turned, Vec3::new(-0.5, 0.0, -1.0)),
+            vec![IVec2::new(-1, -2), IVec2::new(-1, -1)]
+        );
+    }
+
+    #[test]
+    fn cell_centres_map_back_to_their_cell() {
+        let cell = IVec2::new(-3, 4);
+
+        assert_eq!(world_to_cell(cell_to_world(cell)), cell);
+        assert_eq!(footprint_cells(UVec2::ONE, cell_to_world(cell)), vec![cell]);
+    }
+}
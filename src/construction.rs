@@ -0,0 +1,159 @@
+use bevy::{color::palettes, prelude::*};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    building::{finish_building, Building, BuildingAssets, BuildingKind},
+    building_grid::BuildingGrid,
+    interaction::{Interactable, InteractionEvent, InteractionKind},
+    tool::{EquippedTool, ToolKind, ToolUsedEvent},
+};
+
+// Seconds of work put in with each completed interaction
+const CONSTRUCT_INTERVAL: f32 = 0.5;
+const HAMMER_SPEEDUP: f32 = 2.0;
+const PROGRESS_BAR_WIDTH: f32 = 0.8;
+
+pub struct ConstructionPlugin;
+
+impl Plugin for ConstructionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                construct_buildings.run_if(on_event::<InteractionEvent>()),
+                draw_construction_progress,
+            ),
+        );
+    }
+}
+
+// Placed building that still has to be worked on before it can be used
+#[derive(Component, Default)]
+pub struct ConstructionSite {
+    pub progress: f32,
+}
+
+impl ConstructionSite {
+    pub fn fraction(&self, kind: BuildingKind) -> f32 {
+        (self.progress / kind.build_time()).min(1.0)
+    }
+}
+
+fn construct_prompt(kind: BuildingKind, fraction: f32) -> String {
+    format!("Build {} ({:.0}%)", kind.name(), fraction * 100.0)
+}
+
+// Construction sites take up their cells but can be walked through
+pub fn spawn_construction_site(
+    commands: &mut Commands,
+    building_assets: &BuildingAssets,
+    kind: BuildingKind,
+    transform: Transform,
+) -> Entity {
+    spawn_partial_construction_site(
+        commands,
+        building_assets,
+        kind,
+        transform,
+        ConstructionSite::default(),
+    )
+}
+
+// Brings back a site with the work already put into it
+pub fn spawn_partial_construction_site(
+    commands: &mut Commands,
+    building_assets: &BuildingAssets,
+    kind: BuildingKind,
+    transform: Transform,
+    site: ConstructionSite,
+) -> Entity {
+    let prompt = construct_prompt(kind, site.fraction(kind));
+
+    commands
+        .spawn(PbrBundle {
+            mesh: building_assets.mesh(kind),
+            material: building_assets.scaffold_material(),
+            transform,
+            ..Default::default()
+        })
+        .insert((
+            RigidBody::Fixed,
+            kind.collider(),
+            Sensor,
+            Building::new(kind, transform),
+            site,
+            Name::new(format!("{} (site)", kind.name())),
+            Interactable::new(InteractionKind::Construct, prompt).with_duration(CONSTRUCT_INTERVAL),
+        ))
+        .id()
+}
+
+fn construct_buildings(
+    mut commands: Commands,
+    mut sites: Query<(&mut ConstructionSite, &Building, &mut Interactable)>,
+    actors: Query<&EquippedTool>,
+    mut interaction_events: EventReader<InteractionEvent>,
+    mut tool_used_event: EventWriter<ToolUsedEvent>,
+    mut building_grid: ResMut<BuildingGrid>,
+    building_assets: Res<BuildingAssets>,
+) {
+    for interaction_event in interaction_events.read() {
+        if interaction_event.kind != InteractionKind::Construct {
+            continue;
+        }
+
+        let Ok((mut site, building, mut interactable)) = sites.get_mut(interaction_event.target)
+        else {
+            continue;
+        };
+
+        let hammering = actors
+            .get(interaction_event.actor)
+            .is_ok_and(|equipped_tool| equipped_tool.kind() == Some(ToolKind::Hammer));
+        if hammering {
+            site.progress += CONSTRUCT_INTERVAL * HAMMER_SPEEDUP;
+            tool_used_event.send(ToolUsedEvent {
+                actor: interaction_event.actor,
+            });
+        } else {
+            site.progress += CONSTRUCT_INTERVAL;
+        }
+
+        let kind = building.kind;
+        if site.progress < kind.build_time() {
+            interactable.prompt = construct_prompt(kind, site.fraction(kind));
+            continue;
+        }
+
+        let mut entity = commands.entity(interaction_event.target);
+        entity
+            .remove::<(ConstructionSite, Sensor)>()
+            .insert((building_assets.material(kind), Name::new(kind.name())));
+        finish_building(&mut entity, kind);
+
+        // Lets finished walls and fences join up with their neighbours
+        building_grid.set_changed();
+    }
+}
+
+fn draw_construction_progress(
+    mut gizmos: Gizmos,
+    sites: Query<(&ConstructionSite, &Building, &GlobalTransform)>,
+) {
+    for (site, building, global_transform) in &sites {
+        let position =
+            global_transform.translation() + Vec3::Y * (building.kind.size().y / 2.0 + 0.3);
+        let start = position - Vec3::X * PROGRESS_BAR_WIDTH / 2.0;
+
+        gizmos.line(
+            start,
+            start + Vec3::X * PROGRESS_BAR_WIDTH,
+            Color::srgba(0.5, 0.5, 0.5, 0.5),
+        );
+        gizmos.line(
+            start,
+            start + Vec3::X * PROGRESS_BAR_WIDTH * site.fraction(building.kind),
+            palettes::basic::LIME,
+        );
+    }
+}
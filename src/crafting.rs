@@ -0,0 +1,283 @@
+use std::{collections::VecDeque, fs, path::Path};
+
+use bevy::{asset::io::file::FileAssetReader, prelude::*};
+use serde::Deserialize;
+
+use crate::{
+    crafting_ui::CraftingUiPlugin,
+    inventory::Inventory,
+    item::{ItemId, ItemRegistry},
+    pickup::DropItemEvent,
+};
+
+const RECIPE_DIRECTORY: &str = "assets/recipes";
+const WORKSTATION_RANGE: f32 = 3.0;
+pub const MAX_QUEUED_CRAFTS: usize = 8;
+
+pub struct CraftingPlugin;
+
+impl Plugin for CraftingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(CraftingUiPlugin)
+            .init_resource::<RecipeRegistry>()
+            .add_event::<QueueCraftEvent>()
+            .add_event::<CancelCraftEvent>()
+            .add_systems(
+                Update,
+                (
+                    queue_crafts.run_if(on_event::<QueueCraftEvent>()),
+                    cancel_crafts.run_if(on_event::<CancelCraftEvent>()),
+                    update_crafting_queues,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug, Deserialize)]
+pub enum Workstation {
+    Workbench,
+    Campfire,
+}
+
+impl Workstation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Workstation::Workbench => "Workbench",
+            Workstation::Campfire => "Campfire",
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Recipe {
+    pub name: String,
+    pub inputs: Vec<(ItemId, u32)>,
+    pub outputs: Vec<(ItemId, u32)>,
+    pub time: f32,
+    #[serde(default)]
+    pub station: Option<Workstation>,
+}
+
+impl Recipe {
+    pub fn has_inputs(&self, inventory: &Inventory) -> bool {
+        self.inputs
+            .iter()
+            .all(|(item, amount)| inventory.has(*item, *amount))
+    }
+}
+
+#[derive(Resource)]
+pub struct RecipeRegistry {
+    recipes: Vec<Recipe>,
+}
+
+impl RecipeRegistry {
+    pub fn get(&self, recipe: usize) -> &Recipe {
+        &self.recipes[recipe]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Recipe)> {
+        self.recipes.iter().enumerate()
+    }
+
+    // Every `.ron` file in the directory holds a list of recipes
+    fn load_directory(&mut self, directory: &Path) {
+        let entries = match fs::read_dir(directory) {
+            Ok(entries) => entries,
+            Err(error) => {
+                error!("Could not read {}: {}", directory.display(), error);
+                return;
+            }
+        };
+
+        let mut paths: Vec<_> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "ron"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let recipes = fs::read_to_string(&path)
+                .map_err(|error| error.to_string())
+                .and_then(|data| {
+                    ron::from_str::<Vec<Recipe>>(&data).map_err(|error| error.to_string())
+                });
+
+            match recipes {
+                Ok(recipes) => self.recipes.extend(recipes),
+                Err(error) => error!("Could not load recipes from {}: {}", path.display(), error),
+            }
+        }
+    }
+}
+
+impl FromWorld for RecipeRegistry {
+    fn from_world(_world: &mut World) -> Self {
+        let mut registry = RecipeRegistry {
+            recipes: Vec::new(),
+        };
+        registry.load_directory(&FileAssetReader::get_base_path().join(RECIPE_DIRECTORY));
+        registry
+    }
+}
+
+pub struct CraftJob {
+    pub recipe: usize,
+    pub timer: Timer,
+}
+
+// Inputs are taken from the crafter's inventory when a job is queued
+#[derive(Component, Default)]
+pub struct CraftingQueue {
+    pub jobs: VecDeque<CraftJob>,
+}
+
+#[derive(Event)]
+pub struct QueueCraftEvent {
+    pub crafter: Entity,
+    pub recipe: usize,
+}
+
+#[derive(Event)]
+pub struct CancelCraftEvent {
+    pub crafter: Entity,
+    pub index: usize,
+}
+
+pub fn workstation_nearby(
+    position: Vec3,
+    station: Workstation,
+    workstations: &Query<(&Workstation, &GlobalTransform)>,
+) -> bool {
+    workstations.iter().any(|(workstation, transform)| {
+        *workstation == station && transform.translation().distance(position) <= WORKSTATION_RANGE
+    })
+}
+
+pub fn can_craft(
+    recipe: &Recipe,
+    inventory: &Inventory,
+    position: Vec3,
+    workstations: &Query<(&Workstation, &GlobalTransform)>,
+) -> bool {
+    let station_nearby = recipe
+        .station
+        .is_none_or(|station| workstation_nearby(position, station, workstations));
+
+    station_nearby && recipe.has_inputs(inventory)
+}
+
+fn queue_crafts(
+    mut crafters: Query<(&Transform, &mut Inventory, &mut CraftingQueue)>,
+    workstations: Query<(&Workstation, &GlobalTransform)>,
+    mut queue_events: EventReader<QueueCraftEvent>,
+    recipes: Res<RecipeRegistry>,
+) {
+    for queue_event in queue_events.read() {
+        let Ok((crafter_transform, mut inventory, mut queue)) =
+            crafters.get_mut(queue_event.crafter)
+        else {
+            continue;
+        };
+
+        if queue.jobs.len() >= MAX_QUEUED_CRAFTS {
+            continue;
+        }
+
+        let recipe = recipes.get(queue_event.recipe);
+        if !can_craft(
+            recipe,
+            &inventory,
+            crafter_transform.translation,
+            &workstations,
+        ) {
+            continue;
+        }
+
+        for (item, amount) in &recipe.inputs {
+            inventory.remove(*item, *amount);
+        }
+
+        queue.jobs.push_back(CraftJob {
+            recipe: queue_event.recipe,
+            timer: Timer::from_seconds(recipe.time, TimerMode::Once),
+        });
+    }
+}
+
+fn give_items(
+    crafter: Entity,
+    inventory: &mut Inventory,
+    items: &[(ItemId, u32)],
+    registry: &ItemRegistry,
+    drop_event: &mut EventWriter<DropItemEvent>,
+) {
+    for (item, amount) in items {
+        let overflow = inventory.add(registry, *item, *amount);
+        if overflow > 0 {
+            drop_event.send(DropItemEvent {
+                actor: crafter,
+                stack: registry.new_stack(*item, overflow),
+            });
+        }
+    }
+}
+
+// Cancelled jobs give their inputs back
+fn cancel_crafts(
+    mut crafters: Query<(&mut Inventory, &mut CraftingQueue)>,
+    mut cancel_events: EventReader<CancelCraftEvent>,
+    mut drop_event: EventWriter<DropItemEvent>,
+    recipes: Res<RecipeRegistry>,
+    registry: Res<ItemRegistry>,
+) {
+    for cancel_event in cancel_events.read() {
+        let Ok((mut inventory, mut queue)) = crafters.get_mut(cancel_event.crafter) else {
+            continue;
+        };
+
+        let Some(job) = queue.jobs.remove(cancel_event.index) else {
+            continue;
+        };
+
+        give_items(
+            cancel_event.crafter,
+            &mut inventory,
+            &recipes.get(job.recipe).inputs,
+            &registry,
+            &mut drop_event,
+        );
+    }
+}
+
+fn update_crafting_queues(
+    time: Res<Time>,
+    mut crafters: Query<(Entity, &mut Inventory, &mut CraftingQueue)>,
+    mut drop_event: EventWriter<DropItemEvent>,
+    recipes: Res<RecipeRegistry>,
+    registry: Res<ItemRegistry>,
+) {
+    for (crafter, mut inventory, mut queue) in &mut crafters {
+        let Some(job) = queue.jobs.front_mut() else {
+            continue;
+        };
+
+        job.timer.tick(time.delta());
+        if !job.timer.finished() {
+            continue;
+        }
+
+        let recipe = recipes.get(job.recipe);
+        queue.jobs.pop_front();
+
+        give_items(
+            crafter,
+            &mut inventory,
+            &recipe.outputs,
+            &registry,
+            &mut drop_event,
+        );
+    }
+}
@@ -0,0 +1,320 @@
+use bevy::{color::palettes, ecs::system::EntityCommands, prelude::*};
+
+use crate::{
+    crafting::{
+        can_craft, CancelCraftEvent, CraftingQueue, QueueCraftEvent, RecipeRegistry, Workstation,
+        MAX_QUEUED_CRAFTS,
+    },
+    inventory::Inventory,
+    item::{ItemId, ItemRegistry},
+    player::{Player, PlayerState},
+    player_input::{InputParam, PlayerAction},
+};
+
+const AVAILABLE_COLOR: Color = Color::srgb(0.25, 0.35, 0.25);
+const UNAVAILABLE_COLOR: Color = Color::srgb(0.25, 0.25, 0.25);
+const HOVERED_COLOR: Color = Color::srgb(0.35, 0.45, 0.35);
+
+pub struct CraftingUiPlugin;
+
+impl Plugin for CraftingUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_hud)
+            .add_systems(OnEnter(PlayerState::Crafting), spawn_crafting_screen)
+            .add_systems(OnExit(PlayerState::Crafting), despawn_crafting_screen)
+            .add_systems(
+                Update,
+                (
+                    open_crafting.run_if(in_state(PlayerState::Normal)),
+                    close_crafting.run_if(in_state(PlayerState::Crafting)),
+                    (
+                        press_recipe_buttons,
+                        press_queue_buttons,
+                        update_recipe_buttons,
+                        update_queue_buttons,
+                    )
+                        .chain()
+                        .run_if(in_state(PlayerState::Crafting)),
+                    update_hud,
+                ),
+            );
+    }
+}
+
+#[derive(Component)]
+struct CraftingScreen;
+
+#[derive(Component)]
+struct RecipeButton(usize);
+
+#[derive(Component)]
+struct QueueButton(usize);
+
+#[derive(Component)]
+struct CraftingHud;
+
+fn text_style(font_size: f32) -> TextStyle {
+    TextStyle {
+        font_size,
+        color: palettes::basic::WHITE.into(),
+        ..Default::default()
+    }
+}
+
+fn describe_items(items: &[(ItemId, u32)], registry: &ItemRegistry) -> String {
+    items
+        .iter()
+        .map(|(item, amount)| format!("{} {}", amount, registry.name(*item)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn open_crafting(input: InputParam, mut next_player_state: ResMut<NextState<PlayerState>>) {
+    if input.action_just_pressed(PlayerAction::Crafting) {
+        next_player_state.set(PlayerState::Crafting);
+    }
+}
+
+fn close_crafting(input: InputParam, mut next_player_state: ResMut<NextState<PlayerState>>) {
+    if input.action_just_pressed(PlayerAction::Crafting)
+        || input.action_just_pressed(PlayerAction::Cancel)
+    {
+        next_player_state.set(PlayerState::Normal);
+    }
+}
+
+fn spawn_panel<'a>(builder: &'a mut ChildBuilder, title: &str) -> EntityCommands<'a> {
+    let mut panel = builder.spawn(NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::all(Val::Px(10.0)),
+            row_gap: Val::Px(6.0),
+            width: Val::Px(380.0),
+            ..Default::default()
+        },
+        background_color: Color::srgba(0.15, 0.15, 0.15, 0.9).into(),
+        ..Default::default()
+    });
+
+    panel.with_children(|builder| {
+        builder.spawn(TextBundle::from_section(title, text_style(24.0)));
+    });
+
+    panel
+}
+
+fn spawn_crafting_screen(
+    mut commands: Commands,
+    recipes: Res<RecipeRegistry>,
+    registry: Res<ItemRegistry>,
+) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(40.0),
+                ..Default::default()
+            },
+            background_color: Color::srgba(0.0, 0.0, 0.0, 0.5).into(),
+            ..Default::default()
+        })
+        .insert(CraftingScreen)
+        .with_children(|builder| {
+            spawn_panel(builder, "Recipes").with_children(|builder| {
+                for (index, recipe) in recipes.iter() {
+                    let mut details = format!(
+                        "{} -> {}",
+                        describe_items(&recipe.inputs, &registry),
+                        describe_items(&recipe.outputs, &registry)
+                    );
+                    if let Some(station) = recipe.station {
+                        details += &format!("\nRequires {}", station.name());
+                    }
+
+                    builder
+                        .spawn(ButtonBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                padding: UiRect::all(Val::Px(6.0)),
+                                ..Default::default()
+                            },
+                            background_color: UNAVAILABLE_COLOR.into(),
+                            ..Default::default()
+                        })
+                        .insert(RecipeButton(index))
+                        .with_children(|builder| {
+                            builder.spawn(TextBundle::from_section(
+                                format!("{} ({:.1}s)", recipe.name, recipe.time),
+                                text_style(20.0),
+                            ));
+                            builder.spawn(TextBundle::from_section(details, text_style(14.0)));
+                        });
+                }
+            });
+
+            spawn_panel(builder, "Queue").with_children(|builder| {
+                for index in 0..MAX_QUEUED_CRAFTS {
+                    builder
+                        .spawn(ButtonBundle {
+                            style: Style {
+                                padding: UiRect::all(Val::Px(6.0)),
+                                ..Default::default()
+                            },
+                            background_color: UNAVAILABLE_COLOR.into(),
+                            ..Default::default()
+                        })
+                        .insert(QueueButton(index))
+                        .with_children(|builder| {
+                            builder.spawn(TextBundle::from_section("", text_style(16.0)));
+                        });
+                }
+            });
+        });
+}
+
+fn despawn_crafting_screen(mut commands: Commands, screens: Query<Entity, With<CraftingScreen>>) {
+    for screen in &screens {
+        commands.entity(screen).despawn_recursive();
+    }
+}
+
+fn press_recipe_buttons(
+    buttons: Query<(&Interaction, &RecipeButton), Changed<Interaction>>,
+    player: Query<Entity, With<Player>>,
+    mut queue_event: EventWriter<QueueCraftEvent>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction == Interaction::Pressed {
+            queue_event.send(QueueCraftEvent {
+                crafter: player.single(),
+                recipe: button.0,
+            });
+        }
+    }
+}
+
+// Clicking a queued job cancels it
+fn press_queue_buttons(
+    buttons: Query<(&Interaction, &QueueButton), Changed<Interaction>>,
+    player: Query<Entity, With<Player>>,
+    mut cancel_event: EventWriter<CancelCraftEvent>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction == Interaction::Pressed {
+            cancel_event.send(CancelCraftEvent {
+                crafter: player.single(),
+                index: button.0,
+            });
+        }
+    }
+}
+
+fn update_recipe_buttons(
+    mut buttons: Query<(&RecipeButton, &Interaction, &mut BackgroundColor)>,
+    player: Query<(&Transform, &Inventory), With<Player>>,
+    workstations: Query<(&Workstation, &GlobalTransform)>,
+    recipes: Res<RecipeRegistry>,
+) {
+    let Ok((player_transform, inventory)) = player.get_single() else {
+        return;
+    };
+
+    for (button, interaction, mut background_color) in &mut buttons {
+        let recipe = recipes.get(button.0);
+        let color = if !can_craft(
+            recipe,
+            inventory,
+            player_transform.translation,
+            &workstations,
+        ) {
+            UNAVAILABLE_COLOR
+        } else if *interaction == Interaction::Hovered {
+            HOVERED_COLOR
+        } else {
+            AVAILABLE_COLOR
+        };
+
+        if background_color.0 != color {
+            background_color.0 = color;
+        }
+    }
+}
+
+fn update_queue_buttons(
+    mut buttons: Query<(&QueueButton, &mut Style, &Children)>,
+    mut texts: Query<&mut Text>,
+    player: Query<&CraftingQueue, With<Player>>,
+    recipes: Res<RecipeRegistry>,
+) {
+    let Ok(queue) = player.get_single() else {
+        return;
+    };
+
+    for (button, mut style, children) in &mut buttons {
+        let Some(job) = queue.jobs.get(button.0) else {
+            if style.display != Display::None {
+                style.display = Display::None;
+            }
+            continue;
+        };
+
+        if style.display != Display::Flex {
+            style.display = Display::Flex;
+        }
+
+        let recipe = recipes.get(job.recipe);
+        let value = if button.0 == 0 {
+            format!("{} {:.0}%", recipe.name, job.timer.fraction() * 100.0)
+        } else {
+            recipe.name.clone()
+        };
+
+        if let Some(mut text) = children
+            .first()
+            .and_then(|child| texts.get_mut(*child).ok())
+        {
+            if text.sections[0].value != value {
+                text.sections[0].value = value;
+            }
+        }
+    }
+}
+
+fn setup_hud(mut commands: Commands) {
+    commands
+        .spawn(
+            TextBundle::from_section("", text_style(20.0)).with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(8.0),
+                right: Val::Px(12.0),
+                ..Default::default()
+            }),
+        )
+        .insert(CraftingHud);
+}
+
+fn update_hud(
+    mut hud: Query<&mut Text, With<CraftingHud>>,
+    player: Query<&CraftingQueue, (With<Player>, Changed<CraftingQueue>)>,
+    recipes: Res<RecipeRegistry>,
+) {
+    let Ok(queue) = player.get_single() else {
+        return;
+    };
+
+    let mut hud_text = hud.single_mut();
+
+    hud_text.sections[0].value = match queue.jobs.front() {
+        Some(job) => format!(
+            "Crafting {} {:.0}% ({} queued)",
+            recipes.get(job.recipe).name,
+            job.timer.fraction() * 100.0,
+            queue.jobs.len() - 1
+        ),
+        None => String::new(),
+    };
+}
@@ -0,0 +1,307 @@
+use bevy::{color::palettes, prelude::*, render::primitives::Aabb, window::PrimaryWindow};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    build::MAX_BUILD_DISTANCE,
+    building::Building,
+    inventory::Inventory,
+    item::{ItemId, ItemRegistry},
+    pickup::DropItemEvent,
+    player::{Player, PlayerState},
+    player_input::{InputMap, InputParam, PlayerAction},
+};
+
+const MAX_PICK_DISTANCE: f32 = 100.0;
+const HOVER_COLOR: Srgba = palettes::css::TOMATO;
+
+pub struct DeconstructPlugin;
+
+impl Plugin for DeconstructPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DeconstructRefund>()
+            .init_resource::<HoveredBuilding>()
+            .add_event::<DeconstructEvent>()
+            .add_systems(OnEnter(PlayerState::Deconstructing), setup_ui)
+            .add_systems(OnExit(PlayerState::Deconstructing), cleanup)
+            .add_systems(
+                Update,
+                (
+                    start_deconstructing.run_if(in_state(PlayerState::Normal)),
+                    (
+                        stop_deconstructing,
+                        hover_building,
+                        select_building,
+                        draw_hovered_building,
+                        update_ui,
+                    )
+                        .chain()
+                        .run_if(in_state(PlayerState::Deconstructing)),
+                    deconstruct_buildings.run_if(on_event::<DeconstructEvent>()),
+                ),
+            );
+    }
+}
+
+// Fraction of a building's cost given back when it is taken down
+#[derive(Resource)]
+pub struct DeconstructRefund(pub f32);
+
+impl Default for DeconstructRefund {
+    fn default() -> Self {
+        Self(0.5)
+    }
+}
+
+impl DeconstructRefund {
+    pub fn refund(&self, cost: &[(ItemId, u32)]) -> Vec<(ItemId, u32)> {
+        cost.iter()
+            .map(|(item, amount)| (*item, (*amount as f32 * self.0).floor() as u32))
+            .filter(|(_, amount)| *amount > 0)
+            .collect()
+    }
+}
+
+#[derive(Event)]
+pub struct DeconstructEvent {
+    pub actor: Entity,
+    pub building: Entity,
+}
+
+#[derive(Resource, Default)]
+struct HoveredBuilding(Option<Entity>);
+
+#[derive(Component)]
+struct DeconstructText;
+
+fn start_deconstructing(input: InputParam, mut next_player_state: ResMut<NextState<PlayerState>>) {
+    if input.action_just_pressed(PlayerAction::Deconstruct) {
+        next_player_state.set(PlayerState::Deconstructing);
+    }
+}
+
+fn stop_deconstructing(input: InputParam, mut next_player_state: ResMut<NextState<PlayerState>>) {
+    if input.action_just_pressed(PlayerAction::Deconstruct)
+        || input.action_just_pressed(PlayerAction::Cancel)
+    {
+        next_player_state.set(PlayerState::Normal);
+    }
+}
+
+// Picks the building under the cursor, if the player can reach it
+fn hover_building(
+    window: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    player: Query<&Transform, With<Player>>,
+    buildings: Query<&GlobalTransform, With<Building>>,
+    rapier_context: Res<RapierContext>,
+    mut hovered_building: ResMut<HoveredBuilding>,
+) {
+    let Ok(player_transform) = player.get_single() else {
+        return;
+    };
+    let is_building = |entity| buildings.contains(entity);
+    let in_reach = |entity: &Entity| {
+        buildings.get(*entity).is_ok_and(|building_transform| {
+            building_transform
+                .translation()
+                .xz()
+                .distance(player_transform.translation.xz())
+                <= MAX_BUILD_DISTANCE
+        })
+    };
+
+    let hovered = window
+        .get_single()
+        .ok()
+        .and_then(Window::cursor_position)
+        .zip(camera.get_single().ok())
+        .and_then(|(cursor_position, (camera, camera_transform))| {
+            camera.viewport_to_world(camera_transform, cursor_position)
+        })
+        .and_then(|ray| {
+            rapier_context.cast_ray(
+                ray.origin,
+                *ray.direction,
+                MAX_PICK_DISTANCE,
+                true,
+                QueryFilter::new().predicate(&is_building),
+            )
+        })
+        .map(|(entity, _)| entity)
+        .filter(in_reach);
+
+    if hovered_building.0 != hovered {
+        hovered_building.0 = hovered;
+    }
+}
+
+fn select_building(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    player: Query<Entity, With<Player>>,
+    hovered_building: Res<HoveredBuilding>,
+    mut deconstruct_event: EventWriter<DeconstructEvent>,
+) {
+    if !mouse_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    if let (Some(building), Ok(actor)) = (hovered_building.0, player.get_single()) {
+        deconstruct_event.send(DeconstructEvent { actor, building });
+    }
+}
+
+pub fn deconstruct_buildings(
+    mut commands: Commands,
+    mut actors: Query<&mut Inventory>,
+    buildings: Query<&Building>,
+    mut deconstruct_events: EventReader<DeconstructEvent>,
+    mut drop_event: EventWriter<DropItemEvent>,
+    refund: Res<DeconstructRefund>,
+    registry: Res<ItemRegistry>,
+) {
+    for deconstruct_event in deconstruct_events.read() {
+        let Ok(building) = buildings.get(deconstruct_event.building) else {
+            continue;
+        };
+        let Ok(mut inventory) = actors.get_mut(deconstruct_event.actor) else {
+            continue;
+        };
+
+        for (item, amount) in refund.refund(&building.kind.cost()) {
+            let overflow = inventory.add(&registry, item, amount);
+            if overflow > 0 {
+                drop_event.send(DropItemEvent {
+                    actor: deconstruct_event.actor,
+                    stack: registry.new_stack(item, overflow),
+                });
+            }
+        }
+
+        commands
+            .entity(deconstruct_event.building)
+            .despawn_recursive();
+    }
+}
+
+fn draw_hovered_building(
+    mut gizmos: Gizmos,
+    hovered_building: Res<HoveredBuilding>,
+    buildings: Query<(&GlobalTransform, &Aabb), With<Building>>,
+) {
+    let Some((global_transform, aabb)) = hovered_building
+        .0
+        .and_then(|building| buildings.get(building).ok())
+    else {
+        return;
+    };
+
+    let outline = Transform::from_translation(aabb.center.into())
+        .with_scale(Vec3::from(aabb.half_extents) * 2.1);
+    gizmos.cuboid(global_transform.mul_transform(outline), HOVER_COLOR);
+}
+
+fn setup_ui(mut commands: Commands) {
+    commands
+        .spawn(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 20.0,
+                    color: palettes::basic::WHITE.into(),
+                    ..Default::default()
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(8.0),
+                left: Val::Px(8.0),
+                ..Default::default()
+            }),
+        )
+        .insert(DeconstructText);
+}
+
+fn cleanup(
+    mut commands: Commands,
+    texts: Query<Entity, With<DeconstructText>>,
+    mut hovered_building: ResMut<HoveredBuilding>,
+) {
+    for text in &texts {
+        commands.entity(text).despawn_recursive();
+    }
+    hovered_building.0 = None;
+}
+
+fn update_ui(
+    mut text: Query<&mut Text, With<DeconstructText>>,
+    buildings: Query<&Building>,
+    hovered_building: Res<HoveredBuilding>,
+    refund: Res<DeconstructRefund>,
+    registry: Res<ItemRegistry>,
+    input_map: Res<InputMap>,
+) {
+    let Ok(mut text) = text.get_single_mut() else {
+        return;
+    };
+
+    let mut value = format!(
+        "Deconstructing ({:.0}% refund)  [Click] remove  [{}] [{}] done",
+        refund.0 * 100.0,
+        input_map.key_label(PlayerAction::Deconstruct),
+        input_map.key_label(PlayerAction::Cancel),
+    );
+    if let Some(building) = hovered_building
+        .0
+        .and_then(|building| buildings.get(building).ok())
+    {
+        let items: Vec<_> = refund
+            .refund(&building.kind.cost())
+            .iter()
+            .map(|(item, amount)| format!("{} {}", amount, registry.name(*item)))
+            .collect();
+        value += &format!("\n{}: {}", building.kind.name(), items.join(", "));
+    }
+
+    if text.sections[0].value != value {
+        text.sections[0].value = value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refund_rounds_down() {
+        let refund = DeconstructRefund(0.5);
+
+        assert_eq!(
+            refund.refund(&[(ItemId::Wood, 5), (ItemId::Stone, 4)]),
+            vec![(ItemId::Wood, 2), (ItemId::Stone, 2)]
+        );
+        assert_eq!(
+            DeconstructRefund(0.75).refund(&[(ItemId::Wood, 3)]),
+            vec![(ItemId::Wood, 2)]
+        );
+    }
+
+    #[test]
+    fn refund_leaves_out_items_rounded_to_nothing() {
+        let refund = DeconstructRefund(0.5);
+
+        assert_eq!(
+            refund.refund(&[(ItemId::Rope, 1), (ItemId::Wood, 3)]),
+            vec![(ItemId::Wood, 1)]
+        );
+        assert!(DeconstructRefund(0.0)
+            .refund(&[(ItemId::Wood, 10)])
+            .is_empty());
+    }
+
+    #[test]
+    fn full_refund_gives_back_the_cost() {
+        let cost = [(ItemId::Wood, 7), (ItemId::Stone, 3)];
+
+        assert_eq!(DeconstructRefund(1.0).refund(&cost), cost.to_vec());
+    }
+}
@@ -0,0 +1,197 @@
+use bevy::{color::palettes, prelude::*};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    building::{Building, BuildingKind},
+    faction::Faction,
+    health::Health,
+    hitbox::{Hitbox, SpawnHitboxEvent, Target},
+};
+
+const TURRET_RANGE: f32 = 8.0;
+const TURRET_RELOAD: f32 = 1.5;
+const TURRET_DAMAGE: i32 = 3;
+// How long the arrow's path stays visible after a shot
+const TRACER_DURATION: f32 = 0.15;
+const SPIKE_COOLDOWN: f32 = 1.0;
+const SPIKE_DAMAGE: i32 = 4;
+const GATE_RANGE: f32 = 1.5;
+
+pub struct DefensePlugin;
+
+impl Plugin for DefensePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                (fire_turrets, draw_turret_shots).chain(),
+                trigger_spike_traps,
+                update_gates,
+            ),
+        );
+    }
+}
+
+// Shoots arrows at the nearest hostile in range
+#[derive(Component)]
+pub struct Turret {
+    reload: Timer,
+    target: Option<Entity>,
+}
+
+impl Default for Turret {
+    fn default() -> Self {
+        Self {
+            reload: Timer::from_seconds(TURRET_RELOAD, TimerMode::Once),
+            target: None,
+        }
+    }
+}
+
+// Hurts hostiles standing on it
+#[derive(Component)]
+pub struct SpikeTrap {
+    cooldown: Timer,
+}
+
+impl Default for SpikeTrap {
+    fn default() -> Self {
+        Self {
+            cooldown: Timer::from_seconds(SPIKE_COOLDOWN, TimerMode::Once),
+        }
+    }
+}
+
+// Opens for the player's faction and stays shut for anyone else
+#[derive(Component, Default)]
+pub struct Gate {
+    pub open: bool,
+}
+
+fn fire_turrets(
+    time: Res<Time>,
+    mut turrets: Query<(Entity, &mut Turret, &GlobalTransform)>,
+    targets: Query<(Entity, &Faction, &Health, &GlobalTransform)>,
+    mut spawn_hitbox_event: EventWriter<SpawnHitboxEvent>,
+) {
+    for (entity, mut turret, turret_transform) in &mut turrets {
+        turret.reload.tick(time.delta());
+        if !turret.reload.finished() {
+            continue;
+        }
+
+        let turret_position = turret_transform.translation();
+        let nearest = targets
+            .iter()
+            .filter(|(_, faction, health, _)| **faction == Faction::Hostile && health.is_alive())
+            .map(|(target, _, _, transform)| {
+                let distance = transform.translation().distance(turret_position);
+                (target, transform.translation(), distance)
+            })
+            .filter(|(_, _, distance)| *distance <= TURRET_RANGE)
+            .min_by(|(_, _, distance_1), (_, _, distance_2)| distance_1.total_cmp(distance_2));
+
+        let Some((target, position, _)) = nearest else {
+            continue;
+        };
+
+        spawn_hitbox_event.send(SpawnHitboxEvent(Hitbox {
+            sender: entity,
+            collider: Collider::ball(0.3),
+            position,
+            target: Target::Single(target),
+            damage: TURRET_DAMAGE,
+            lifetime: Timer::from_seconds(0.1, TimerMode::Once),
+        }));
+
+        turret.target = Some(target);
+        turret.reload.reset();
+    }
+}
+
+fn draw_turret_shots(
+    mut gizmos: Gizmos,
+    turrets: Query<(&Turret, &GlobalTransform)>,
+    targets: Query<&GlobalTransform>,
+) {
+    let top = Vec3::Y * BuildingKind::Turret.size().y / 2.0;
+
+    for (turret, turret_transform) in &turrets {
+        if turret.reload.elapsed_secs() > TRACER_DURATION {
+            continue;
+        }
+        let Some(target_transform) = turret.target.and_then(|target| targets.get(target).ok())
+        else {
+            continue;
+        };
+
+        gizmos.line(
+            turret_transform.translation() + top,
+            target_transform.translation(),
+            palettes::css::WHEAT,
+        );
+    }
+}
+
+fn trigger_spike_traps(
+    time: Res<Time>,
+    mut traps: Query<(Entity, &mut SpikeTrap, &Building, &GlobalTransform)>,
+    targets: Query<(&Faction, &GlobalTransform), With<Health>>,
+    mut spawn_hitbox_event: EventWriter<SpawnHitboxEvent>,
+) {
+    for (entity, mut trap, building, trap_transform) in &mut traps {
+        trap.cooldown.tick(time.delta());
+        if !trap.cooldown.finished() {
+            continue;
+        }
+
+        let position = trap_transform.translation();
+        let half_size = building.kind.size() / 2.0;
+        let stepped_on = targets.iter().any(|(faction, transform)| {
+            let offset = (transform.translation() - position).abs();
+            *faction == Faction::Hostile && offset.x <= half_size.x && offset.z <= half_size.z
+        });
+        if !stepped_on {
+            continue;
+        }
+
+        spawn_hitbox_event.send(SpawnHitboxEvent(Hitbox {
+            sender: entity,
+            collider: Collider::cuboid(half_size.x, 0.5, half_size.z),
+            position,
+            target: Target::Enemies,
+            damage: SPIKE_DAMAGE,
+            lifetime: Timer::from_seconds(0.1, TimerMode::Once),
+        }));
+
+        trap.cooldown.reset();
+    }
+}
+
+fn update_gates(
+    mut commands: Commands,
+    mut gates: Query<(Entity, &mut Gate, &mut Visibility, &Transform)>,
+    factions: Query<(&Faction, &GlobalTransform)>,
+) {
+    for (entity, mut gate, mut visibility, transform) in &mut gates {
+        let position = transform.translation.xz();
+        let friendly_nearby = factions.iter().any(|(faction, faction_transform)| {
+            *faction == Faction::Player
+                && faction_transform.translation().xz().distance(position) <= GATE_RANGE
+        });
+
+        if gate.open == friendly_nearby {
+            continue;
+        }
+        gate.open = friendly_nearby;
+
+        // Open gates are drawn away and can be walked through
+        if gate.open {
+            *visibility = Visibility::Hidden;
+            commands.entity(entity).insert(Sensor);
+        } else {
+            *visibility = Visibility::Inherited;
+            commands.entity(entity).remove::<Sensor>();
+        }
+    }
+}
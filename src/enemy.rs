@@ -4,12 +4,16 @@ use std::{f32::consts::PI, time::Duration};
 use bevy::{color::palettes, prelude::*};
 use bevy_rapier3d::prelude::*;
 
+use crate::{faction::Faction, health::Health, inventory::Inventory};
+
 pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup)
-            .add_systems(Update, (update_move_timer, move_enemy));
+        app.add_systems(Startup, setup).add_systems(
+            Update,
+            (update_move_timer, move_enemy, despawn_dead_enemies),
+        );
     }
 }
 
@@ -83,7 +87,13 @@ fn spawn_enemy(
             LockedAxes::ROTATION_LOCKED,
             Velocity::zero(),
         ))
-        .insert((Enemy, EnemyAI::new()));
+        .insert((
+            Enemy,
+            Faction::Hostile,
+            EnemyAI::new(),
+            Health::new_full(10),
+        ))
+        .insert(Inventory::new(4));
 }
 
 fn update_move_timer(time: Res<Time>, mut enemies: Query<(&Transform, &mut EnemyAI)>) {
@@ -129,3 +139,11 @@ fn move_enemy(time: Res<Time>, mut enemies: Query<(&mut Velocity, &mut EnemyAI,
         enemy_velocity.linvel = to_target * enemy_speed * dt;
     }
 }
+
+fn despawn_dead_enemies(mut commands: Commands, enemies: Query<(Entity, &Health), With<Enemy>>) {
+    for (entity, health) in &enemies {
+        if health.is_dead() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
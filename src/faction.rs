@@ -0,0 +1,8 @@
+use bevy::prelude::*;
+
+// Side an entity fights on, deciding what defences attack and let through
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Faction {
+    Player,
+    Hostile,
+}
@@ -1,5 +1,9 @@
 use bevy::prelude::*;
 
+// Ignores incoming hits, e.g. while dodging
+#[derive(Component)]
+pub struct Invulnerable;
+
 #[derive(Component)]
 pub struct Health {
     max: i32,
@@ -1,6 +1,11 @@
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 
+use crate::{
+    enemy::Enemy,
+    health::{Health, Invulnerable},
+};
+
 pub struct HitboxPlugin;
 
 impl Plugin for HitboxPlugin {
@@ -9,6 +14,7 @@ impl Plugin for HitboxPlugin {
             Update,
             (
                 spawn_hitbox.run_if(on_event::<SpawnHitboxEvent>()),
+                apply_hitbox_damage.run_if(on_event::<CollisionEvent>()),
                 update_hitbox_timer,
                 despawn_hitbox,
             ),
@@ -32,6 +38,7 @@ pub struct Hitbox {
     pub collider: Collider,
     pub position: Vec3,
     pub target: Target,
+    pub damage: i32,
     pub lifetime: Timer,
 }
 
@@ -41,11 +48,56 @@ fn spawn_hitbox(mut commands: Commands, mut spawn_events: EventReader<SpawnHitbo
             .spawn(TransformBundle::from_transform(
                 Transform::from_translation(spawn_event.0.position),
             ))
-            .insert((spawn_event.0.collider.clone(), Sensor))
+            .insert((
+                spawn_event.0.collider.clone(),
+                Sensor,
+                ActiveEvents::COLLISION_EVENTS,
+            ))
             .insert(spawn_event.0.clone());
     }
 }
 
+fn apply_hitbox_damage(
+    hitboxes: Query<&Hitbox>,
+    mut targets: Query<(&mut Health, Has<Enemy>, Has<Invulnerable>)>,
+    mut collision_events: EventReader<CollisionEvent>,
+) {
+    for collision_event in collision_events.read() {
+        let CollisionEvent::Started(entity_1, entity_2, _) = collision_event else {
+            continue;
+        };
+
+        let (hitbox, target_entity) = match hitboxes.get(*entity_1) {
+            Ok(hitbox) => (hitbox, *entity_2),
+            Err(_) => match hitboxes.get(*entity_2) {
+                Ok(hitbox) => (hitbox, *entity_1),
+                Err(_) => continue,
+            },
+        };
+
+        if target_entity == hitbox.sender {
+            continue;
+        }
+
+        let Ok((mut health, is_enemy, is_invulnerable)) = targets.get_mut(target_entity) else {
+            continue;
+        };
+        if is_invulnerable {
+            continue;
+        }
+
+        let hit = match hitbox.target {
+            Target::Single(entity) => entity == target_entity,
+            Target::Enemies => is_enemy,
+            Target::All => true,
+        };
+
+        if hit {
+            health.change(-hitbox.damage);
+        }
+    }
+}
+
 fn update_hitbox_timer(time: Res<Time>, mut hitboxes: Query<&mut Hitbox>) {
     let dt = time.delta();
     for mut hitbox in &mut hitboxes {
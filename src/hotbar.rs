@@ -0,0 +1,313 @@
+use bevy::{color::palettes, input::mouse::MouseWheel, prelude::*};
+
+use crate::{
+    inventory::Inventory,
+    item::ItemRegistry,
+    pickup::DropItemEvent,
+    player::{Player, PlayerState},
+    player_input::{InputParam, PlayerAction},
+    tool::{EquippedTool, ToolUsedEvent},
+};
+
+pub const HOTBAR_SIZE: usize = 6;
+const SLOT_SIZE: f32 = 52.0;
+
+pub struct HotbarPlugin;
+
+impl Plugin for HotbarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ToolUsedEvent>()
+            .add_systems(Startup, setup_ui)
+            .add_systems(
+                Update,
+                (
+                    (select_with_keys, select_with_mouse_wheel, drop_selected)
+                        .run_if(in_state(PlayerState::Normal)),
+                    wear_tools.run_if(on_event::<ToolUsedEvent>()),
+                    sync_equipped_tool,
+                    update_ui,
+                )
+                    .chain(),
+            );
+    }
+}
+
+// The first `HOTBAR_SIZE` slots of the owner's inventory
+#[derive(Component, Default)]
+pub struct Hotbar {
+    pub selected: usize,
+}
+
+#[derive(Component)]
+struct HotbarSlotUi(usize);
+
+#[derive(Component)]
+struct HotbarIcon;
+
+#[derive(Component)]
+struct HotbarCount;
+
+#[derive(Component)]
+struct DurabilityBar;
+
+fn select_with_keys(input: InputParam, mut hotbar: Query<&mut Hotbar, With<Player>>) {
+    let Ok(mut hotbar) = hotbar.get_single_mut() else {
+        return;
+    };
+
+    if let Some(index) =
+        (0..HOTBAR_SIZE).find(|index| input.action_just_pressed(PlayerAction::HotbarSlot(*index)))
+    {
+        hotbar.selected = index;
+    }
+}
+
+fn select_with_mouse_wheel(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut mouse_wheel: EventReader<MouseWheel>,
+    mut hotbar: Query<&mut Hotbar, With<Player>>,
+) {
+    // Ctrl + wheel is reserved for camera zoom
+    if keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        mouse_wheel.clear();
+        return;
+    }
+
+    let Ok(mut hotbar) = hotbar.get_single_mut() else {
+        return;
+    };
+
+    for mouse_wheel in mouse_wheel.read() {
+        if mouse_wheel.y < 0.0 {
+            hotbar.selected = (hotbar.selected + 1) % HOTBAR_SIZE;
+        } else if mouse_wheel.y > 0.0 {
+            hotbar.selected = (hotbar.selected + HOTBAR_SIZE - 1) % HOTBAR_SIZE;
+        }
+    }
+}
+
+fn drop_selected(
+    input: InputParam,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut player: Query<(Entity, &Hotbar, &mut Inventory), With<Player>>,
+    mut drop_event: EventWriter<DropItemEvent>,
+) {
+    if !input.action_just_pressed(PlayerAction::Drop) {
+        return;
+    }
+
+    let Ok((player, hotbar, mut inventory)) = player.get_single_mut() else {
+        return;
+    };
+
+    let Some(stack) = inventory.slot(hotbar.selected) else {
+        return;
+    };
+
+    // Ctrl drops the whole stack, otherwise a single item
+    let dropped = if keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        stack.count
+    } else {
+        1
+    };
+
+    inventory.set_slot(
+        hotbar.selected,
+        Some(stack.with_count(stack.count - dropped)),
+    );
+    drop_event.send(DropItemEvent {
+        actor: player,
+        stack: stack.with_count(dropped),
+    });
+}
+
+fn sync_equipped_tool(
+    mut holders: Query<(&Hotbar, &Inventory, &mut EquippedTool)>,
+    registry: Res<ItemRegistry>,
+) {
+    for (hotbar, inventory, mut equipped_tool) in &mut holders {
+        let tool = inventory
+            .slot(hotbar.selected)
+            .and_then(|stack| registry.tool(stack.item));
+
+        equipped_tool.set_if_neq(EquippedTool(tool));
+    }
+}
+
+fn wear_tools(
+    mut holders: Query<(&Hotbar, &mut Inventory)>,
+    mut tool_used_events: EventReader<ToolUsedEvent>,
+    registry: Res<ItemRegistry>,
+) {
+    for tool_used_event in tool_used_events.read() {
+        let Ok((hotbar, mut inventory)) = holders.get_mut(tool_used_event.actor) else {
+            continue;
+        };
+
+        let Some(mut stack) = inventory.slot(hotbar.selected) else {
+            continue;
+        };
+
+        let Some(durability) = stack.durability else {
+            continue;
+        };
+
+        if durability <= 1 {
+            info!("{} broke", registry.name(stack.item));
+            inventory.set_slot(hotbar.selected, None);
+            continue;
+        }
+
+        stack.durability = Some(durability - 1);
+        inventory.set_slot(hotbar.selected, Some(stack));
+    }
+}
+
+fn setup_ui(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(12.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(4.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with_children(|builder| {
+            for index in 0..HOTBAR_SIZE {
+                spawn_slot(builder, index);
+            }
+        });
+}
+
+fn spawn_slot(builder: &mut ChildBuilder, index: usize) {
+    builder
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(SLOT_SIZE),
+                height: Val::Px(SLOT_SIZE),
+                border: UiRect::all(Val::Px(3.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            background_color: Color::srgba(0.2, 0.2, 0.2, 0.8).into(),
+            ..Default::default()
+        })
+        .insert(HotbarSlotUi(index))
+        .with_children(|builder| {
+            builder.spawn((
+                ImageBundle {
+                    style: Style {
+                        width: Val::Px(SLOT_SIZE - 14.0),
+                        height: Val::Px(SLOT_SIZE - 14.0),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                HotbarIcon,
+            ));
+
+            builder.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 14.0,
+                        color: palettes::basic::WHITE.into(),
+                        ..Default::default()
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(3.0),
+                    bottom: Val::Px(1.0),
+                    ..Default::default()
+                }),
+                HotbarCount,
+            ));
+
+            builder.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(2.0),
+                        bottom: Val::Px(2.0),
+                        height: Val::Px(3.0),
+                        width: Val::Percent(0.0),
+                        ..Default::default()
+                    },
+                    background_color: palettes::basic::LIME.into(),
+                    ..Default::default()
+                },
+                DurabilityBar,
+            ));
+        });
+}
+
+fn update_ui(
+    player: Query<(&Hotbar, &Inventory), With<Player>>,
+    mut slots: Query<(&HotbarSlotUi, &mut BorderColor, &Children)>,
+    mut icons: Query<(&mut UiImage, &mut Visibility), With<HotbarIcon>>,
+    mut counts: Query<&mut Text, With<HotbarCount>>,
+    mut durability_bars: Query<&mut Style, With<DurabilityBar>>,
+    registry: Res<ItemRegistry>,
+) {
+    let Ok((hotbar, inventory)) = player.get_single() else {
+        return;
+    };
+
+    for (slot, mut border_color, children) in &mut slots {
+        let stack = inventory.slot(slot.0);
+
+        let color: Color = if slot.0 == hotbar.selected {
+            palettes::basic::WHITE.into()
+        } else {
+            Color::srgb(0.1, 0.1, 0.1)
+        };
+        if border_color.0 != color {
+            border_color.0 = color;
+        }
+
+        for child in children {
+            if let Ok((mut icon, mut visibility)) = icons.get_mut(*child) {
+                match stack {
+                    Some(stack) => {
+                        let texture = &registry.get(stack.item).icon;
+                        if icon.texture != *texture {
+                            icon.texture = texture.clone();
+                        }
+                        visibility.set_if_neq(Visibility::Inherited);
+                    }
+                    None => {
+                        visibility.set_if_neq(Visibility::Hidden);
+                    }
+                }
+            }
+
+            if let Ok(mut count) = counts.get_mut(*child) {
+                let value = match stack {
+                    Some(stack) if stack.count > 1 => stack.count.to_string(),
+                    _ => String::new(),
+                };
+                if count.sections[0].value != value {
+                    count.sections[0].value = value;
+                }
+            }
+
+            if let Ok(mut style) = durability_bars.get_mut(*child) {
+                let fraction = stack
+                    .and_then(|stack| Some((stack.durability?, registry.tool(stack.item)?)))
+                    .map_or(0.0, |(durability, tool)| {
+                        durability as f32 / tool.max_durability as f32
+                    });
+                let width = Val::Percent(fraction * 90.0);
+                if style.width != width {
+                    style.width = width;
+                }
+            }
+        }
+    }
+}
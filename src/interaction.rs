@@ -0,0 +1,258 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    color::palettes,
+    ecs::component::{ComponentHooks, StorageType},
+    prelude::*,
+    render::primitives::Aabb,
+};
+
+use crate::player::Player;
+
+// Sizes of the progress ring in screen pixels
+const PROGRESS_RING_RADIUS: f32 = 28.0;
+const PROGRESS_RING_SEGMENTS: usize = 16;
+const PROGRESS_SEGMENT_SIZE: f32 = 7.0;
+const PROGRESS_EMPTY_COLOR: Color = Color::srgba(0.5, 0.5, 0.5, 0.5);
+const HIGHLIGHT_COLOR: Srgba = palettes::basic::LIME;
+
+pub struct InteractionPlugin;
+
+impl Plugin for InteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<InteractionEvent>()
+            .init_resource::<InteractableEntities>()
+            .add_systems(Startup, setup_progress_ring)
+            .add_systems(
+                Update,
+                (
+                    (update_interaction_progress, update_progress_ring).chain(),
+                    draw_highlight_outlines,
+                ),
+            );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum InteractionKind {
+    Harvest,
+    Open,
+    Loot,
+    Talk,
+    Repair,
+    Construct,
+}
+
+#[derive(Event)]
+pub struct InteractionEvent {
+    pub actor: Entity,
+    pub target: Entity,
+    pub kind: InteractionKind,
+}
+
+#[derive(Component)]
+pub struct InteractionProgress {
+    pub target: Entity,
+    pub kind: InteractionKind,
+    pub timer: Timer,
+}
+
+impl InteractionProgress {
+    pub fn new(target: Entity, kind: InteractionKind, duration: f32) -> Self {
+        Self {
+            target,
+            kind,
+            timer: Timer::from_seconds(duration, TimerMode::Repeating),
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct InteractableEntities {
+    pub interactables: Vec<Entity>,
+}
+
+#[derive(Component)]
+pub struct Highlighted;
+
+#[derive(Component)]
+struct ProgressRing;
+
+// Segments fill clockwise from the top as the interaction progresses
+#[derive(Component)]
+struct ProgressRingSegment(usize);
+
+pub struct Interactable {
+    pub kinds: Vec<InteractionKind>,
+    pub prompt: String,
+    pub duration: f32,
+}
+
+impl Component for Interactable {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    // Runs on both removal and despawn, so tracked entities can never dangle
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks.on_remove(|mut world, entity, _| {
+            if let Some(mut interactable_entities) =
+                world.get_resource_mut::<InteractableEntities>()
+            {
+                interactable_entities
+                    .interactables
+                    .retain(|interactable| *interactable != entity);
+            }
+        });
+    }
+}
+
+impl Interactable {
+    pub fn new(kind: InteractionKind, prompt: impl Into<String>) -> Self {
+        Self {
+            kinds: vec![kind],
+            prompt: prompt.into(),
+            duration: 0.0,
+        }
+    }
+
+    pub fn with_kind(mut self, kind: InteractionKind) -> Self {
+        self.kinds.push(kind);
+        self
+    }
+
+    pub fn with_duration(mut self, duration: f32) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    pub fn primary_kind(&self) -> Option<InteractionKind> {
+        self.kinds.first().copied()
+    }
+
+    pub fn accepts(&self, kind: InteractionKind) -> bool {
+        self.kinds.contains(&kind)
+    }
+}
+
+fn update_interaction_progress(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut actors: Query<(Entity, &mut InteractionProgress)>,
+    interactables: Query<&Interactable>,
+    mut interaction_event: EventWriter<InteractionEvent>,
+) {
+    for (actor, mut progress) in &mut actors {
+        let target_accepts = interactables
+            .get(progress.target)
+            .is_ok_and(|interactable| interactable.accepts(progress.kind));
+        if !target_accepts {
+            commands.entity(actor).remove::<InteractionProgress>();
+            continue;
+        }
+
+        progress.timer.tick(time.delta());
+
+        // Holding keeps repeating the interaction, e.g. one chop per completed ring
+        for _ in 0..progress.timer.times_finished_this_tick() {
+            interaction_event.send(InteractionEvent {
+                actor,
+                target: progress.target,
+                kind: progress.kind,
+            });
+        }
+    }
+}
+
+fn setup_progress_ring(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Px(PROGRESS_RING_RADIUS * 2.0),
+                height: Val::Px(PROGRESS_RING_RADIUS * 2.0),
+                ..Default::default()
+            },
+            visibility: Visibility::Hidden,
+            ..Default::default()
+        })
+        .insert(ProgressRing)
+        .with_children(|builder| {
+            for index in 0..PROGRESS_RING_SEGMENTS {
+                let angle = index as f32 / PROGRESS_RING_SEGMENTS as f32 * TAU;
+                let position = Vec2::new(angle.sin(), -angle.cos()) * PROGRESS_RING_RADIUS
+                    + Vec2::splat(PROGRESS_RING_RADIUS - PROGRESS_SEGMENT_SIZE / 2.0);
+
+                builder
+                    .spawn(NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            left: Val::Px(position.x),
+                            top: Val::Px(position.y),
+                            width: Val::Px(PROGRESS_SEGMENT_SIZE),
+                            height: Val::Px(PROGRESS_SEGMENT_SIZE),
+                            ..Default::default()
+                        },
+                        background_color: PROGRESS_EMPTY_COLOR.into(),
+                        ..Default::default()
+                    })
+                    .insert(ProgressRingSegment(index));
+            }
+        });
+}
+
+// Centres the ring on the player's interaction target
+fn update_progress_ring(
+    player: Query<&InteractionProgress, With<Player>>,
+    targets: Query<&GlobalTransform>,
+    camera: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    mut ring: Query<(&mut Style, &mut Visibility), With<ProgressRing>>,
+    mut segments: Query<(&ProgressRingSegment, &mut BackgroundColor)>,
+) {
+    let Ok((mut style, mut visibility)) = ring.get_single_mut() else {
+        return;
+    };
+
+    let shown = player.get_single().ok().and_then(|progress| {
+        let target_transform = targets.get(progress.target).ok()?;
+        let (camera, camera_transform) = camera.get_single().ok()?;
+        let position =
+            camera.world_to_viewport(camera_transform, target_transform.translation())?;
+        Some((position, progress.timer.fraction()))
+    });
+
+    let Some((position, fraction)) = shown else {
+        if *visibility != Visibility::Hidden {
+            *visibility = Visibility::Hidden;
+        }
+        return;
+    };
+
+    if *visibility != Visibility::Inherited {
+        *visibility = Visibility::Inherited;
+    }
+    style.left = Val::Px(position.x - PROGRESS_RING_RADIUS);
+    style.top = Val::Px(position.y - PROGRESS_RING_RADIUS);
+
+    let filled = (fraction * PROGRESS_RING_SEGMENTS as f32) as usize;
+    for (segment, mut background_color) in &mut segments {
+        let color = if segment.0 < filled {
+            Color::from(palettes::basic::WHITE)
+        } else {
+            PROGRESS_EMPTY_COLOR
+        };
+        if background_color.0 != color {
+            background_color.0 = color;
+        }
+    }
+}
+
+fn draw_highlight_outlines(
+    mut gizmos: Gizmos,
+    highlighted: Query<(&GlobalTransform, &Aabb), With<Highlighted>>,
+) {
+    for (global_transform, aabb) in &highlighted {
+        let outline = Transform::from_translation(aabb.center.into())
+            .with_scale(Vec3::from(aabb.half_extents) * 2.1);
+
+        gizmos.cuboid(global_transform.mul_transform(outline), HIGHLIGHT_COLOR);
+    }
+}
@@ -1,58 +1,336 @@
 use bevy::{color::palettes, prelude::*};
 
+use crate::{
+    inventory_ui::InventoryUiPlugin,
+    item::{ItemId, ItemRegistry, ItemStack},
+    player::Player,
+};
+
 pub struct InventoryPlugin;
 
 impl Plugin for InventoryPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<Inventory>()
+        app.add_plugins(InventoryUiPlugin)
             .add_systems(Startup, setup_ui)
             .add_systems(Update, update_ui);
     }
 }
 
-#[derive(Resource)]
+#[derive(Component, Clone)]
 pub struct Inventory {
-    pub wood: u32,
+    slots: Vec<Option<ItemStack>>,
 }
 
-#[derive(Component)]
-struct WoodText;
+impl Inventory {
+    pub fn new(size: usize) -> Self {
+        Self {
+            slots: vec![None; size],
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn slots(&self) -> &[Option<ItemStack>] {
+        &self.slots
+    }
+
+    pub fn slot(&self, index: usize) -> Option<ItemStack> {
+        self.slots.get(index).copied().flatten()
+    }
+
+    // Returns false if there is no slot at `index`
+    pub fn set_slot(&mut self, index: usize, stack: Option<ItemStack>) -> bool {
+        let Some(slot) = self.slots.get_mut(index) else {
+            return false;
+        };
+
+        *slot = stack.filter(|stack| stack.count > 0);
+        true
+    }
+
+    pub fn take_slot(&mut self, index: usize) -> Option<ItemStack> {
+        self.slots.get_mut(index).and_then(Option::take)
+    }
+
+    pub fn swap_slots(&mut self, index_1: usize, index_2: usize) -> bool {
+        if index_1 >= self.slots.len() || index_2 >= self.slots.len() {
+            return false;
+        }
+
+        self.slots.swap(index_1, index_2);
+        true
+    }
+
+    pub fn count(&self, item: ItemId) -> u32 {
+        self.slots
+            .iter()
+            .flatten()
+            .filter(|stack| stack.item == item)
+            .map(|stack| stack.count)
+            .sum()
+    }
+
+    pub fn wood(&self) -> u32 {
+        self.count(ItemId::Wood)
+    }
+
+    pub fn has(&self, item: ItemId, amount: u32) -> bool {
+        self.count(item) >= amount
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(Option::is_none)
+    }
+
+    pub fn space_for(&self, registry: &ItemRegistry, item: ItemId) -> u32 {
+        let max_stack = registry.max_stack(item);
+
+        self.slots
+            .iter()
+            .map(|slot| match slot {
+                Some(stack) if stack.item == item => max_stack.saturating_sub(stack.count),
+                Some(_) => 0,
+                None => max_stack,
+            })
+            .sum()
+    }
+
+    // Returns how many items did not fit
+    pub fn add(&mut self, registry: &ItemRegistry, item: ItemId, amount: u32) -> u32 {
+        self.add_stack(registry, registry.new_stack(item, amount))
+    }
+
+    // Like `add` but keeps per-stack data such as tool durability
+    pub fn add_stack(&mut self, registry: &ItemRegistry, stack: ItemStack) -> u32 {
+        let max_stack = registry.max_stack(stack.item);
+        let mut remaining = stack.count;
+
+        for existing in self.slots.iter_mut().flatten() {
+            if remaining == 0 {
+                break;
+            }
+
+            if existing.stacks_with(&stack) && existing.count < max_stack {
+                let added = u32::min(max_stack - existing.count, remaining);
+                existing.count += added;
+                remaining -= added;
+            }
+        }
+
+        for slot in self.slots.iter_mut() {
+            if remaining == 0 {
+                break;
+            }
+
+            if slot.is_none() {
+                let added = u32::min(max_stack, remaining);
+                *slot = Some(stack.with_count(added));
+                remaining -= added;
+            }
+        }
+
+        remaining
+    }
+
+    // Adds a stack into a specific slot, merging with a matching stack, and returns the leftover
+    pub fn add_to_slot(
+        &mut self,
+        registry: &ItemRegistry,
+        index: usize,
+        stack: ItemStack,
+    ) -> Option<ItemStack> {
+        let max_stack = registry.max_stack(stack.item);
+        let Some(slot) = self.slots.get_mut(index) else {
+            return Some(stack);
+        };
+
+        match slot {
+            slot @ None => {
+                let added = u32::min(max_stack, stack.count);
+                *slot = Some(stack.with_count(added));
+                Some(stack.with_count(stack.count - added))
+            }
+            Some(existing) if existing.stacks_with(&stack) => {
+                let added = u32::min(max_stack.saturating_sub(existing.count), stack.count);
+                existing.count += added;
+                Some(stack.with_count(stack.count - added))
+            }
+            Some(_) => Some(stack),
+        }
+        .filter(|leftover| leftover.count > 0)
+    }
 
-impl Default for Inventory {
-    fn default() -> Self {
-        Self { wood: 0 }
+    // Only removes the items if the full amount is available
+    pub fn remove(&mut self, item: ItemId, amount: u32) -> bool {
+        if !self.has(item, amount) {
+            return false;
+        }
+
+        let mut remaining = amount;
+        for slot in self.slots.iter_mut().rev() {
+            if remaining == 0 {
+                break;
+            }
+
+            let Some(stack) = slot else {
+                continue;
+            };
+
+            if stack.item != item {
+                continue;
+            }
+
+            let removed = u32::min(stack.count, remaining);
+            stack.count -= removed;
+            remaining -= removed;
+
+            if stack.count == 0 {
+                *slot = None;
+            }
+        }
+
+        true
+    }
+
+    // Moves the stack in `index` to `other`, keeping whatever does not fit
+    pub fn transfer(&mut self, registry: &ItemRegistry, index: usize, other: &mut Inventory) {
+        let Some(stack) = self.take_slot(index) else {
+            return;
+        };
+
+        let leftover = other.add_stack(registry, stack);
+        if leftover > 0 {
+            self.set_slot(index, Some(stack.with_count(leftover)));
+        }
+    }
+
+    pub fn transfer_all(&mut self, registry: &ItemRegistry, other: &mut Inventory) {
+        for index in 0..self.slots.len() {
+            self.transfer(registry, index, other);
+        }
     }
 }
 
+#[derive(Component)]
+struct InventoryText;
+
 fn setup_ui(mut commands: Commands) {
     commands
-        .spawn(TextBundle::from_sections([
-            TextSection {
-                value: String::from("Wood: "),
-                style: TextStyle {
-                    font_size: 30.0,
-                    color: palettes::basic::GREEN.into(),
-                    ..Default::default()
-                },
+        .spawn(TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 30.0,
+                color: palettes::basic::GREEN.into(),
+                ..Default::default()
             },
-            TextSection {
-                value: String::from("0"),
-                style: TextStyle {
-                    font_size: 30.0,
-                    color: palettes::basic::GREEN.into(),
-                    ..Default::default()
-                },
-            },
-        ]))
-        .insert(WoodText);
+        ))
+        .insert(InventoryText);
 }
 
-fn update_ui(mut ui_text: Query<&mut Text, With<WoodText>>, inventory: Res<Inventory>) {
-    if !inventory.is_changed() {
+fn update_ui(
+    mut ui_text: Query<&mut Text, With<InventoryText>>,
+    inventory: Query<&Inventory, (With<Player>, Changed<Inventory>)>,
+    registry: Res<ItemRegistry>,
+) {
+    let Ok(inventory) = inventory.get_single() else {
         return;
+    };
+
+    let mut inventory_text = ui_text.single_mut();
+
+    inventory_text.sections[0].value = ItemId::ALL
+        .iter()
+        .map(|item| format!("{}: {}\n", registry.name(*item), inventory.count(*item)))
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> ItemRegistry {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Image>();
+        ItemRegistry::from_world(app.world_mut())
+    }
+
+    #[test]
+    fn add_stack_returns_overflow() {
+        let registry = registry();
+        let mut inventory = Inventory::new(2);
+
+        let overflow = inventory.add(&registry, ItemId::Wood, 120);
+
+        assert_eq!(overflow, 20);
+        assert_eq!(inventory.wood(), 100);
+        assert_eq!(inventory.space_for(&registry, ItemId::Wood), 0);
+    }
+
+    #[test]
+    fn add_stack_fills_existing_stacks_first() {
+        let registry = registry();
+        let mut inventory = Inventory::new(3);
+        inventory.set_slot(2, Some(ItemStack::new(ItemId::Stone, 40)));
+
+        let overflow = inventory.add(&registry, ItemId::Stone, 15);
+
+        assert_eq!(overflow, 0);
+        assert_eq!(inventory.slot(2), Some(ItemStack::new(ItemId::Stone, 50)));
+        assert_eq!(inventory.slot(0), Some(ItemStack::new(ItemId::Stone, 5)));
+    }
+
+    #[test]
+    fn remove_is_all_or_nothing() {
+        let registry = registry();
+        let mut inventory = Inventory::new(3);
+        inventory.add(&registry, ItemId::Wood, 60);
+
+        assert!(!inventory.remove(ItemId::Wood, 61));
+        assert_eq!(inventory.wood(), 60);
+
+        assert!(inventory.remove(ItemId::Wood, 60));
+        assert!(inventory.is_empty());
+    }
+
+    #[test]
+    fn add_to_slot_returns_leftover() {
+        let registry = registry();
+        let mut inventory = Inventory::new(2);
+        inventory.set_slot(0, Some(ItemStack::new(ItemId::Wood, 45)));
+
+        let leftover = inventory.add_to_slot(&registry, 0, ItemStack::new(ItemId::Wood, 10));
+        assert_eq!(leftover, Some(ItemStack::new(ItemId::Wood, 5)));
+        assert_eq!(inventory.slot(0), Some(ItemStack::new(ItemId::Wood, 50)));
+
+        let stone = ItemStack::new(ItemId::Stone, 3);
+        assert_eq!(inventory.add_to_slot(&registry, 0, stone), Some(stone));
+        assert_eq!(inventory.add_to_slot(&registry, 5, stone), Some(stone));
+        assert_eq!(inventory.add_to_slot(&registry, 1, stone), None);
     }
 
-    let mut wood_text = ui_text.single_mut();
+    #[test]
+    fn transfer_keeps_leftover() {
+        let registry = registry();
+        let mut inventory = Inventory::new(1);
+        let mut other = Inventory::new(1);
+        inventory.set_slot(0, Some(ItemStack::new(ItemId::Wood, 30)));
+        other.set_slot(0, Some(ItemStack::new(ItemId::Wood, 40)));
 
-    wood_text.sections[1].value = inventory.wood.to_string();
+        inventory.transfer(&registry, 0, &mut other);
+
+        assert_eq!(other.wood(), 50);
+        assert_eq!(inventory.slot(0), Some(ItemStack::new(ItemId::Wood, 20)));
+    }
+
+    #[test]
+    fn out_of_range_slots_are_ignored() {
+        let mut inventory = Inventory::new(2);
+
+        assert!(!inventory.set_slot(2, Some(ItemStack::new(ItemId::Wood, 1))));
+        assert!(!inventory.swap_slots(0, 2));
+        assert!(inventory.is_empty());
+    }
 }
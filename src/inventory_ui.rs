@@ -0,0 +1,692 @@
+use bevy::{
+    color::palettes, ecs::system::SystemParam, prelude::*, ui::RelativeCursorPosition,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    inventory::Inventory,
+    item::{ItemRegistry, ItemStack},
+    pickup::DropItemEvent,
+    player::{Player, PlayerState},
+    player_input::{InputParam, PlayerAction},
+};
+
+const SLOT_SIZE: f32 = 56.0;
+const ICON_SIZE: f32 = 40.0;
+const SLOTS_PER_ROW: usize = 6;
+
+pub struct InventoryUiPlugin;
+
+impl Plugin for InventoryUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OpenContainers>()
+            .init_resource::<DraggedStack>()
+            .add_systems(OnEnter(PlayerState::Inventory), spawn_inventory_screen)
+            .add_systems(
+                OnExit(PlayerState::Inventory),
+                (return_dragged_stack, despawn_inventory_screen).chain(),
+            )
+            .add_systems(
+                Update,
+                (
+                    open_inventory.run_if(in_state(PlayerState::Normal)),
+                    close_inventory.run_if(in_state(PlayerState::Inventory)),
+                    (
+                        quick_transfer,
+                        press_transfer_all_buttons,
+                        start_drag,
+                        drop_drag,
+                        drop_hovered_slot,
+                        update_slots,
+                        update_drag_icon,
+                        update_tooltip,
+                    )
+                        .chain()
+                        .run_if(in_state(PlayerState::Inventory)),
+                ),
+            );
+    }
+}
+
+// Containers shown side by side while the inventory screen is open, player first
+#[derive(Resource, Default)]
+pub struct OpenContainers(pub Vec<Entity>);
+
+#[derive(Resource, Default)]
+struct DraggedStack(Option<Dragged>);
+
+struct Dragged {
+    container: Entity,
+    index: usize,
+    stack: ItemStack,
+    button: MouseButton,
+}
+
+#[derive(Component)]
+struct InventoryScreen;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+struct SlotUi {
+    container: Entity,
+    index: usize,
+}
+
+#[derive(Component)]
+struct ContainerPanel;
+
+#[derive(Component)]
+struct TransferAllButton {
+    container: Entity,
+}
+
+#[derive(Component)]
+struct SlotIcon;
+
+#[derive(Component)]
+struct SlotCount;
+
+#[derive(Component)]
+struct DragIcon;
+
+#[derive(Component)]
+struct Tooltip;
+
+fn open_inventory(
+    input: InputParam,
+    player: Query<Entity, With<Player>>,
+    mut open_containers: ResMut<OpenContainers>,
+    mut next_player_state: ResMut<NextState<PlayerState>>,
+) {
+    if !input.action_just_pressed(PlayerAction::Inventory) {
+        return;
+    }
+
+    open_containers.0 = vec![player.single()];
+    next_player_state.set(PlayerState::Inventory);
+}
+
+fn close_inventory(input: InputParam, mut next_player_state: ResMut<NextState<PlayerState>>) {
+    if input.action_just_pressed(PlayerAction::Inventory)
+        || input.action_just_pressed(PlayerAction::Cancel)
+    {
+        next_player_state.set(PlayerState::Normal);
+    }
+}
+
+fn text_style(font_size: f32) -> TextStyle {
+    TextStyle {
+        font_size,
+        color: palettes::basic::WHITE.into(),
+        ..Default::default()
+    }
+}
+
+fn spawn_inventory_screen(
+    mut commands: Commands,
+    open_containers: Res<OpenContainers>,
+    containers: Query<(&Inventory, Option<&Name>)>,
+) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(40.0),
+                ..Default::default()
+            },
+            background_color: Color::srgba(0.0, 0.0, 0.0, 0.5).into(),
+            ..Default::default()
+        })
+        .insert(InventoryScreen)
+        .with_children(|builder| {
+            for container in &open_containers.0 {
+                let Ok((inventory, name)) = containers.get(*container) else {
+                    continue;
+                };
+
+                let can_transfer = open_containers.0.len() > 1;
+                spawn_container_panel(builder, *container, inventory, name, can_transfer);
+            }
+        });
+
+    commands
+        .spawn(ImageBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Px(ICON_SIZE),
+                height: Val::Px(ICON_SIZE),
+                ..Default::default()
+            },
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(10),
+            ..Default::default()
+        })
+        .insert((DragIcon, InventoryScreen))
+        .with_children(|builder| {
+            builder.spawn((TextBundle::from_section("", text_style(16.0)), SlotCount));
+        });
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                padding: UiRect::all(Val::Px(6.0)),
+                ..Default::default()
+            },
+            background_color: Color::srgba(0.1, 0.1, 0.1, 0.9).into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(11),
+            ..Default::default()
+        })
+        .insert((Tooltip, InventoryScreen))
+        .with_children(|builder| {
+            builder.spawn(TextBundle::from_section("", text_style(16.0)));
+        });
+}
+
+fn spawn_container_panel(
+    builder: &mut ChildBuilder,
+    container: Entity,
+    inventory: &Inventory,
+    name: Option<&Name>,
+    can_transfer: bool,
+) {
+    builder
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(10.0)),
+                row_gap: Val::Px(8.0),
+                ..Default::default()
+            },
+            background_color: Color::srgba(0.15, 0.15, 0.15, 0.9).into(),
+            ..Default::default()
+        })
+        .insert((ContainerPanel, RelativeCursorPosition::default()))
+        .with_children(|builder| {
+            let title = name.map_or("Container", Name::as_str);
+            builder.spawn(TextBundle::from_section(title, text_style(24.0)));
+
+            builder
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(SLOT_SIZE * SLOTS_PER_ROW as f32),
+                        flex_wrap: FlexWrap::Wrap,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .with_children(|builder| {
+                    for index in 0..inventory.size() {
+                        spawn_slot(builder, SlotUi { container, index });
+                    }
+                });
+
+            if can_transfer {
+                builder
+                    .spawn(ButtonBundle {
+                        style: Style {
+                            padding: UiRect::all(Val::Px(6.0)),
+                            justify_content: JustifyContent::Center,
+                            ..Default::default()
+                        },
+                        background_color: Color::srgb(0.25, 0.25, 0.25).into(),
+                        ..Default::default()
+                    })
+                    .insert(TransferAllButton { container })
+                    .with_children(|builder| {
+                        builder.spawn(TextBundle::from_section("Move all", text_style(18.0)));
+                    });
+            }
+        });
+}
+
+fn spawn_slot(builder: &mut ChildBuilder, slot: SlotUi) {
+    builder
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(SLOT_SIZE),
+                height: Val::Px(SLOT_SIZE),
+                border: UiRect::all(Val::Px(2.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            background_color: Color::srgb(0.25, 0.25, 0.25).into(),
+            border_color: Color::srgb(0.1, 0.1, 0.1).into(),
+            ..Default::default()
+        })
+        .insert((slot, RelativeCursorPosition::default()))
+        .with_children(|builder| {
+            builder.spawn((
+                ImageBundle {
+                    style: Style {
+                        width: Val::Px(ICON_SIZE),
+                        height: Val::Px(ICON_SIZE),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                SlotIcon,
+            ));
+
+            builder.spawn((
+                TextBundle::from_section("", text_style(16.0)).with_style(Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(4.0),
+                    bottom: Val::Px(2.0),
+                    ..Default::default()
+                }),
+                SlotCount,
+            ));
+        });
+}
+
+fn despawn_inventory_screen(mut commands: Commands, screens: Query<Entity, With<InventoryScreen>>) {
+    for screen in &screens {
+        commands.entity(screen).despawn_recursive();
+    }
+}
+
+fn hovered_slot(slots: &Query<(&SlotUi, &RelativeCursorPosition)>) -> Option<SlotUi> {
+    slots
+        .iter()
+        .find(|(_, cursor_position)| cursor_position.mouse_over())
+        .map(|(slot, _)| *slot)
+}
+
+// The container stacks move to when transferred out of `container`
+fn other_container(open_containers: &OpenContainers, container: Entity) -> Option<Entity> {
+    open_containers
+        .0
+        .iter()
+        .copied()
+        .find(|other| *other != container)
+}
+
+fn transfer_between(
+    registry: &ItemRegistry,
+    inventories: &mut Query<&mut Inventory>,
+    from: Entity,
+    to: Entity,
+    index: Option<usize>,
+) {
+    let Ok([mut from_inventory, mut to_inventory]) = inventories.get_many_mut([from, to]) else {
+        return;
+    };
+
+    match index {
+        Some(index) => from_inventory.transfer(registry, index, &mut to_inventory),
+        None => from_inventory.transfer_all(registry, &mut to_inventory),
+    }
+}
+
+// Shift + click moves a whole stack to the other open container
+fn quick_transfer(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    slots: Query<(&SlotUi, &RelativeCursorPosition)>,
+    mut inventories: Query<&mut Inventory>,
+    open_containers: Res<OpenContainers>,
+    registry: Res<ItemRegistry>,
+) {
+    if !keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+        || !mouse_input.just_pressed(MouseButton::Left)
+    {
+        return;
+    }
+
+    let Some(slot) = hovered_slot(&slots) else {
+        return;
+    };
+
+    let Some(other) = other_container(&open_containers, slot.container) else {
+        return;
+    };
+
+    transfer_between(
+        &registry,
+        &mut inventories,
+        slot.container,
+        other,
+        Some(slot.index),
+    );
+}
+
+fn press_transfer_all_buttons(
+    buttons: Query<(&Interaction, &TransferAllButton), Changed<Interaction>>,
+    mut inventories: Query<&mut Inventory>,
+    open_containers: Res<OpenContainers>,
+    registry: Res<ItemRegistry>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        let Some(other) = other_container(&open_containers, button.container) else {
+            continue;
+        };
+
+        transfer_between(&registry, &mut inventories, button.container, other, None);
+    }
+}
+
+fn start_drag(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    slots: Query<(&SlotUi, &RelativeCursorPosition)>,
+    mut inventories: Query<&mut Inventory>,
+    mut dragged_stack: ResMut<DraggedStack>,
+) {
+    // Shift + click is handled by `quick_transfer`
+    if dragged_stack.0.is_some()
+        || keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+    {
+        return;
+    }
+
+    let button = if mouse_input.just_pressed(MouseButton::Left) {
+        MouseButton::Left
+    } else if mouse_input.just_pressed(MouseButton::Right) {
+        MouseButton::Right
+    } else {
+        return;
+    };
+
+    let Some(slot) = hovered_slot(&slots) else {
+        return;
+    };
+
+    let Ok(mut inventory) = inventories.get_mut(slot.container) else {
+        return;
+    };
+
+    let Some(stack) = inventory.slot(slot.index) else {
+        return;
+    };
+
+    // Right click splits off half of the stack, rounded up
+    let taken = match button {
+        MouseButton::Right => stack.count.div_ceil(2),
+        _ => stack.count,
+    };
+    inventory.set_slot(slot.index, Some(stack.with_count(stack.count - taken)));
+
+    dragged_stack.0 = Some(Dragged {
+        container: slot.container,
+        index: slot.index,
+        stack: stack.with_count(taken),
+        button,
+    });
+}
+
+fn drop_drag(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    slots: Query<(&SlotUi, &RelativeCursorPosition)>,
+    panels: Query<&RelativeCursorPosition, With<ContainerPanel>>,
+    mut inventories: Query<&mut Inventory>,
+    mut dragged_stack: ResMut<DraggedStack>,
+    mut stack_dropper: StackDropper,
+    registry: Res<ItemRegistry>,
+) {
+    let Some(dragged) = &dragged_stack.0 else {
+        return;
+    };
+
+    if !mouse_input.just_released(dragged.button) {
+        return;
+    }
+
+    let dragged = dragged_stack.0.take().unwrap();
+    let mut leftover = Some(dragged.stack);
+
+    // Releasing outside of every panel throws the stack into the world next to its owner
+    if !panels.iter().any(RelativeCursorPosition::mouse_over) {
+        stack_dropper.drop(dragged.container, dragged.stack);
+        return;
+    }
+
+    if let Some(target) = hovered_slot(&slots) {
+        let source_slot_empty = inventories
+            .get(dragged.container)
+            .is_ok_and(|inventory| inventory.slot(dragged.index).is_none());
+
+        if let Ok(mut target_inventory) = inventories.get_mut(target.container) {
+            match target_inventory.slot(target.index) {
+                Some(existing) if !existing.stacks_with(&dragged.stack) => {
+                    // Different items only swap when the whole source stack was picked up
+                    if source_slot_empty {
+                        target_inventory.set_slot(target.index, Some(dragged.stack));
+                        leftover = Some(existing);
+                    }
+                }
+                _ => {
+                    leftover = target_inventory.add_to_slot(&registry, target.index, dragged.stack);
+                }
+            }
+        }
+    }
+
+    let Some(leftover) = leftover else {
+        return;
+    };
+
+    return_to_source(
+        &registry,
+        &mut inventories,
+        &mut stack_dropper,
+        &dragged,
+        leftover,
+    );
+}
+
+fn drop_hovered_slot(
+    input: InputParam,
+    slots: Query<(&SlotUi, &RelativeCursorPosition)>,
+    mut inventories: Query<&mut Inventory>,
+    dragged_stack: Res<DraggedStack>,
+    mut drop_event: EventWriter<DropItemEvent>,
+) {
+    if dragged_stack.0.is_some() || !input.action_just_pressed(PlayerAction::Drop) {
+        return;
+    }
+
+    let Some(slot) = hovered_slot(&slots) else {
+        return;
+    };
+
+    let Some(stack) = inventories
+        .get_mut(slot.container)
+        .ok()
+        .and_then(|mut inventory| inventory.take_slot(slot.index))
+    else {
+        return;
+    };
+
+    drop_event.send(DropItemEvent {
+        actor: slot.container,
+        stack,
+    });
+}
+
+// Throws stacks that no longer fit anywhere into the world
+#[derive(SystemParam)]
+struct StackDropper<'w> {
+    open_containers: Res<'w, OpenContainers>,
+    drop_event: EventWriter<'w, DropItemEvent>,
+}
+
+impl StackDropper<'_> {
+    fn drop(&mut self, actor: Entity, stack: ItemStack) {
+        self.drop_event.send(DropItemEvent { actor, stack });
+    }
+
+    // Used when the container the stack came from is gone
+    fn drop_near_player(&mut self, stack: ItemStack) {
+        if let Some(&player) = self.open_containers.0.first() {
+            self.drop(player, stack);
+        }
+    }
+}
+
+fn return_to_source(
+    registry: &ItemRegistry,
+    inventories: &mut Query<&mut Inventory>,
+    stack_dropper: &mut StackDropper,
+    dragged: &Dragged,
+    stack: ItemStack,
+) {
+    let Ok(mut inventory) = inventories.get_mut(dragged.container) else {
+        stack_dropper.drop_near_player(stack);
+        return;
+    };
+
+    let Some(leftover) = inventory.add_to_slot(registry, dragged.index, stack) else {
+        return;
+    };
+
+    let overflow = inventory.add_stack(registry, leftover);
+    if overflow > 0 {
+        stack_dropper.drop(dragged.container, leftover.with_count(overflow));
+    }
+}
+
+fn return_dragged_stack(
+    mut inventories: Query<&mut Inventory>,
+    mut dragged_stack: ResMut<DraggedStack>,
+    mut stack_dropper: StackDropper,
+    registry: Res<ItemRegistry>,
+) {
+    if let Some(dragged) = dragged_stack.0.take() {
+        return_to_source(
+            &registry,
+            &mut inventories,
+            &mut stack_dropper,
+            &dragged,
+            dragged.stack,
+        );
+    }
+}
+
+fn update_slots(
+    slots: Query<(&SlotUi, &Children)>,
+    inventories: Query<&Inventory>,
+    mut icons: Query<(&mut UiImage, &mut Visibility), With<SlotIcon>>,
+    mut counts: Query<&mut Text, With<SlotCount>>,
+    registry: Res<ItemRegistry>,
+) {
+    for (slot, children) in &slots {
+        let stack = inventories
+            .get(slot.container)
+            .ok()
+            .and_then(|inventory| inventory.slot(slot.index));
+
+        for child in children {
+            if let Ok((mut icon, mut visibility)) = icons.get_mut(*child) {
+                match stack {
+                    Some(stack) => {
+                        let texture = &registry.get(stack.item).icon;
+                        if icon.texture != *texture {
+                            icon.texture = texture.clone();
+                        }
+                        visibility.set_if_neq(Visibility::Inherited);
+                    }
+                    None => {
+                        visibility.set_if_neq(Visibility::Hidden);
+                    }
+                }
+            }
+
+            if let Ok(mut count) = counts.get_mut(*child) {
+                let value = match stack {
+                    Some(stack) if stack.count > 1 => stack.count.to_string(),
+                    _ => String::new(),
+                };
+                if count.sections[0].value != value {
+                    count.sections[0].value = value;
+                }
+            }
+        }
+    }
+}
+
+fn update_drag_icon(
+    window: Query<&Window, With<PrimaryWindow>>,
+    dragged_stack: Res<DraggedStack>,
+    mut drag_icon: Query<(&mut Style, &mut UiImage, &mut Visibility, &Children), With<DragIcon>>,
+    mut counts: Query<&mut Text, With<SlotCount>>,
+    registry: Res<ItemRegistry>,
+) {
+    let Ok((mut style, mut icon, mut visibility, children)) = drag_icon.get_single_mut() else {
+        return;
+    };
+
+    let (Some(dragged), Some(cursor_position)) = (
+        &dragged_stack.0,
+        window.get_single().ok().and_then(Window::cursor_position),
+    ) else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+
+    style.left = Val::Px(cursor_position.x - ICON_SIZE / 2.0);
+    style.top = Val::Px(cursor_position.y - ICON_SIZE / 2.0);
+    icon.texture = registry.get(dragged.stack.item).icon.clone();
+    visibility.set_if_neq(Visibility::Visible);
+
+    for child in children {
+        if let Ok(mut count) = counts.get_mut(*child) {
+            count.sections[0].value = dragged.stack.count.to_string();
+        }
+    }
+}
+
+fn update_tooltip(
+    window: Query<&Window, With<PrimaryWindow>>,
+    slots: Query<(&SlotUi, &RelativeCursorPosition)>,
+    inventories: Query<&Inventory>,
+    dragged_stack: Res<DraggedStack>,
+    mut tooltip: Query<(&mut Style, &mut Visibility, &Children), With<Tooltip>>,
+    mut texts: Query<&mut Text>,
+    registry: Res<ItemRegistry>,
+) {
+    let Ok((mut style, mut visibility, children)) = tooltip.get_single_mut() else {
+        return;
+    };
+
+    let stack = hovered_slot(&slots).and_then(|slot| {
+        inventories
+            .get(slot.container)
+            .ok()
+            .and_then(|inventory| inventory.slot(slot.index))
+    });
+    let cursor_position = window.get_single().ok().and_then(Window::cursor_position);
+
+    let (Some(stack), Some(cursor_position), None) = (stack, cursor_position, &dragged_stack.0)
+    else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+
+    let definition = registry.get(stack.item);
+    style.left = Val::Px(cursor_position.x + 16.0);
+    style.top = Val::Px(cursor_position.y + 16.0);
+    visibility.set_if_neq(Visibility::Visible);
+
+    for child in children {
+        if let Ok(mut text) = texts.get_mut(*child) {
+            let mut tooltip = format!(
+                "{}\n{:?}\n{} / {}",
+                definition.name, definition.category, stack.count, definition.max_stack
+            );
+            if let (Some(durability), Some(tool)) = (stack.durability, definition.tool) {
+                tooltip += &format!("\nDurability {} / {}", durability, tool.max_durability);
+            }
+            text.sections[0].value = tooltip;
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use bevy::{color::palettes, prelude::*, utils::HashMap};
+use serde::Deserialize;
+
+use crate::tool::{ToolKind, ToolStats};
+
+pub struct ItemPlugin;
+
+impl Plugin for ItemPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ItemRegistry>();
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Deserialize)]
+pub enum ItemId {
+    Wood,
+    Stone,
+    Berries,
+    Fibre,
+    Ore,
+    Plank,
+    Rope,
+    Ingot,
+    Axe,
+    Pickaxe,
+    Sword,
+    Hammer,
+}
+
+impl ItemId {
+    pub const ALL: [ItemId; 12] = [
+        ItemId::Wood,
+        ItemId::Stone,
+        ItemId::Berries,
+        ItemId::Fibre,
+        ItemId::Ore,
+        ItemId::Plank,
+        ItemId::Rope,
+        ItemId::Ingot,
+        ItemId::Axe,
+        ItemId::Pickaxe,
+        ItemId::Sword,
+        ItemId::Hammer,
+    ];
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ItemCategory {
+    Resource,
+    Food,
+    Tool,
+}
+
+pub struct ItemDefinition {
+    pub id: ItemId,
+    pub name: &'static str,
+    pub icon: Handle<Image>,
+    pub color: Srgba,
+    pub max_stack: u32,
+    pub category: ItemCategory,
+    pub tool: Option<ToolStats>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ItemStack {
+    pub item: ItemId,
+    pub count: u32,
+    pub durability: Option<u32>,
+}
+
+impl ItemStack {
+    pub fn new(item: ItemId, count: u32) -> Self {
+        Self {
+            item,
+            count,
+            durability: None,
+        }
+    }
+
+    pub fn with_count(&self, count: u32) -> Self {
+        Self { count, ..*self }
+    }
+
+    pub fn stacks_with(&self, other: &ItemStack) -> bool {
+        self.item == other.item && self.durability == other.durability
+    }
+}
+
+#[derive(Resource)]
+pub struct ItemRegistry {
+    items: HashMap<ItemId, ItemDefinition>,
+}
+
+impl ItemRegistry {
+    pub fn get(&self, item: ItemId) -> &ItemDefinition {
+        &self.items[&item]
+    }
+
+    pub fn name(&self, item: ItemId) -> &'static str {
+        self.get(item).name
+    }
+
+    pub fn max_stack(&self, item: ItemId) -> u32 {
+        self.get(item).max_stack
+    }
+
+    pub fn tool(&self, item: ItemId) -> Option<ToolStats> {
+        self.get(item).tool
+    }
+
+    // Fresh stack, with full durability for tools
+    pub fn new_stack(&self, item: ItemId, count: u32) -> ItemStack {
+        ItemStack {
+            durability: self.tool(item).map(|tool| tool.max_durability),
+            ..ItemStack::new(item, count)
+        }
+    }
+
+    fn register(&mut self, definition: ItemDefinition) {
+        self.items.insert(definition.id, definition);
+    }
+}
+
+impl FromWorld for ItemRegistry {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        let mut registry = ItemRegistry {
+            items: HashMap::new(),
+        };
+
+        let mut register = |id, name, color, max_stack, category, tool| {
+            registry.register(ItemDefinition {
+                id,
+                name,
+                icon: asset_server.load(format!("icons/{}.png", name.to_lowercase())),
+                color,
+                max_stack,
+                category,
+                tool,
+            });
+        };
+
+        use ItemCategory::*;
+        let materials = [
+            (
+                ItemId::Wood,
+                "Wood",
+                palettes::css::SADDLE_BROWN,
+                50,
+                Resource,
+            ),
+            (ItemId::Stone, "Stone", palettes::basic::GRAY, 50, Resource),
+            (ItemId::Berries, "Berries", palettes::css::CRIMSON, 20, Food),
+            (ItemId::Fibre, "Fibre", palettes::css::KHAKI, 50, Resource),
+            (ItemId::Ore, "Ore", palettes::css::SIENNA, 30, Resource),
+            (
+                ItemId::Plank,
+                "Plank",
+                palettes::css::BURLYWOOD,
+                50,
+                Resource,
+            ),
+            (ItemId::Rope, "Rope", palettes::css::TAN, 20, Resource),
+            (
+                ItemId::Ingot,
+                "Ingot",
+                palettes::css::LIGHT_STEEL_BLUE,
+                20,
+                Resource,
+            ),
+        ];
+        for (id, name, color, max_stack, category) in materials {
+            register(id, name, color, max_stack, category, None);
+        }
+
+        let tools = [
+            (ItemId::Axe, "Axe", ToolStats::new(ToolKind::Axe, 3, 5, 120)),
+            (
+                ItemId::Pickaxe,
+                "Pickaxe",
+                ToolStats::new(ToolKind::Pickaxe, 2, 4, 120),
+            ),
+            (
+                ItemId::Sword,
+                "Sword",
+                ToolStats::new(ToolKind::Sword, 6, 1, 80),
+            ),
+            (
+                ItemId::Hammer,
+                "Hammer",
+                ToolStats::new(ToolKind::Hammer, 4, 2, 100),
+            ),
+        ];
+        for (id, name, stats) in tools {
+            register(id, name, palettes::css::SILVER, 1, Tool, Some(stats));
+        }
+
+        registry
+    }
+}
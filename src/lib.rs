@@ -1,8 +1,32 @@
+pub mod blueprint;
+pub mod blueprint_ui;
 pub mod build;
+pub mod build_history;
+pub mod build_menu;
+pub mod building;
+pub mod building_connection;
+pub mod building_grid;
+pub mod construction;
+pub mod crafting;
+pub mod crafting_ui;
+pub mod deconstruct;
+pub mod defense;
 pub mod enemy;
+pub mod faction;
 pub mod health;
 pub mod hitbox;
+pub mod hotbar;
+pub mod interaction;
 pub mod inventory;
+pub mod inventory_ui;
+pub mod item;
+pub mod movement;
+pub mod pickup;
 pub mod player;
 pub mod player_input;
+pub mod resource_node;
+pub mod stamina;
+pub mod storage;
+pub mod targeting;
+pub mod tool;
 pub mod tree;
@@ -1,7 +1,7 @@
 use bevy::{input::mouse::MouseWheel, prelude::*};
 use bevy_rapier3d::prelude::*;
 use forrest::{
-    enemy::EnemyPlugin, hitbox::HitboxPlugin, inventory::InventoryPlugin, player::{CameraZoom, PlayerPlugin}, tree::TreePlugin
+    crafting::CraftingPlugin, enemy::EnemyPlugin, hitbox::HitboxPlugin, interaction::InteractionPlugin, inventory::InventoryPlugin, item::ItemPlugin, pickup::PickupPlugin, player::{CameraZoom, PlayerPlugin}, resource_node::ResourceNodePlugin, storage::StoragePlugin, tree::TreePlugin
 };
 
 fn main() {
@@ -9,7 +9,7 @@ fn main() {
         .add_plugins(DefaultPlugins)
         .add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
         .add_plugins(RapierDebugRenderPlugin::default().disabled())
-        .add_plugins((PlayerPlugin, TreePlugin, EnemyPlugin, InventoryPlugin, HitboxPlugin))
+        .add_plugins((PlayerPlugin, InteractionPlugin, TreePlugin, ResourceNodePlugin, EnemyPlugin, InventoryPlugin, ItemPlugin, PickupPlugin, HitboxPlugin, CraftingPlugin, StoragePlugin))
         .add_systems(Startup, setup)
         .add_systems(Update, (exit, toggle_debug_view, handle_zoom))
         .run();
@@ -51,7 +51,17 @@ fn toggle_debug_view(input: Res<ButtonInput<KeyCode>>, mut debug_view: ResMut<De
     }
 }
 
-fn handle_zoom(mut mouse_weel: EventReader<MouseWheel>, mut camera_zoom: ResMut<CameraZoom>) {
+fn handle_zoom(
+    input: Res<ButtonInput<KeyCode>>,
+    mut mouse_weel: EventReader<MouseWheel>,
+    mut camera_zoom: ResMut<CameraZoom>,
+) {
+    // Without Ctrl the wheel cycles the hotbar
+    if !input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        mouse_weel.clear();
+        return;
+    }
+
     for mouse_weel in mouse_weel.read() {
         camera_zoom.0 += mouse_weel.y;
     }
@@ -0,0 +1,106 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+// World gravity is off, so characters fall on their own
+const GRAVITY: f32 = 20.0;
+const MAX_FALL_SPEED: f32 = 20.0;
+const MAX_STEP_HEIGHT: f32 = 0.3;
+
+pub struct MovementPlugin;
+
+impl Plugin for MovementPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, move_characters.in_set(MovementSet));
+    }
+}
+
+// Systems choosing where characters want to go run before this set
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MovementSet;
+
+// Walking character driven through rapier's character controller
+#[derive(Component)]
+pub struct Movement {
+    // Top walking speed in units per second
+    pub speed: f32,
+    pub acceleration: f32,
+    pub deceleration: f32,
+    // Scales the top speed, e.g. while sprinting
+    pub speed_multiplier: f32,
+    // Wanted direction of travel, no longer than one
+    pub direction: Vec3,
+    velocity: Vec3,
+    fall_speed: f32,
+}
+
+impl Movement {
+    pub fn new(speed: f32) -> Self {
+        Self {
+            speed,
+            acceleration: speed * 10.0,
+            deceleration: speed * 8.0,
+            speed_multiplier: 1.0,
+            direction: Vec3::ZERO,
+            velocity: Vec3::ZERO,
+            fall_speed: 0.0,
+        }
+    }
+
+    pub fn velocity(&self) -> Vec3 {
+        self.velocity
+    }
+
+    // Skips acceleration, for sudden bursts like a dodge
+    pub fn set_velocity(&mut self, velocity: Vec3) {
+        self.velocity = velocity;
+    }
+}
+
+pub fn character_controller() -> KinematicCharacterController {
+    KinematicCharacterController {
+        offset: CharacterLength::Absolute(0.02),
+        max_slope_climb_angle: PI / 4.0,
+        min_slope_slide_angle: PI / 6.0,
+        autostep: Some(CharacterAutostep {
+            max_height: CharacterLength::Absolute(MAX_STEP_HEIGHT),
+            min_width: CharacterLength::Absolute(0.2),
+            include_dynamic_bodies: false,
+        }),
+        snap_to_ground: Some(CharacterLength::Absolute(MAX_STEP_HEIGHT)),
+        filter_flags: QueryFilterFlags::EXCLUDE_SENSORS,
+        ..Default::default()
+    }
+}
+
+fn move_characters(
+    time: Res<Time>,
+    mut characters: Query<(
+        &mut Movement,
+        &mut KinematicCharacterController,
+        Option<&KinematicCharacterControllerOutput>,
+    )>,
+) {
+    let dt = time.delta_seconds();
+
+    for (mut movement, mut controller, output) in &mut characters {
+        let target =
+            movement.direction.clamp_length_max(1.0) * movement.speed * movement.speed_multiplier;
+        let rate = if movement.direction == Vec3::ZERO {
+            movement.deceleration
+        } else {
+            movement.acceleration
+        };
+        let change = (target - movement.velocity).clamp_length_max(rate * dt);
+        movement.velocity += change;
+
+        if output.is_some_and(|output| output.grounded) {
+            movement.fall_speed = 0.0;
+        } else {
+            movement.fall_speed = (movement.fall_speed + GRAVITY * dt).min(MAX_FALL_SPEED);
+        }
+
+        controller.translation = Some((movement.velocity - Vec3::Y * movement.fall_speed) * dt);
+    }
+}
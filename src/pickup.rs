@@ -0,0 +1,317 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, time::common_conditions::on_timer, utils::HashMap};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    inventory::Inventory,
+    item::{ItemId, ItemRegistry, ItemStack},
+    player::{Facing, Player, PlayerState},
+    player_input::{InputParam, PlayerAction},
+};
+
+const PICKUP_LIFETIME: f32 = 120.0;
+const DROP_COLLECT_DELAY: f32 = 1.5;
+const COLLECT_RADIUS: f32 = 0.5;
+const INTERACT_COLLECT_RADIUS: f32 = 1.5;
+const MAGNET_RADIUS: f32 = 2.5;
+const MAGNET_SPEED: f32 = 6.0;
+const MERGE_RADIUS: f32 = 0.75;
+
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SpawnPickupEvent>()
+            .add_event::<DropItemEvent>()
+            .add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (
+                    drop_items.run_if(on_event::<DropItemEvent>()),
+                    spawn_pickup.run_if(on_event::<SpawnPickupEvent>()),
+                    update_pickup_timers,
+                    merge_pickups.run_if(on_timer(Duration::from_secs_f32(0.5))),
+                    magnetise_pickups,
+                    collect_pickups,
+                    collect_on_interact.run_if(in_state(PlayerState::Normal)),
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Event)]
+pub struct SpawnPickupEvent {
+    pub stack: ItemStack,
+    pub position: Vec3,
+}
+
+// Drops a stack in front of the actor, which has to be taken out of its inventory beforehand
+#[derive(Event)]
+pub struct DropItemEvent {
+    pub actor: Entity,
+    pub stack: ItemStack,
+}
+
+#[derive(Component)]
+pub struct Pickup {
+    pub stack: ItemStack,
+    lifetime: Timer,
+    collect_delay: Timer,
+}
+
+impl Pickup {
+    fn new(stack: ItemStack, collect_delay: f32) -> Self {
+        Self {
+            stack,
+            lifetime: Timer::from_seconds(PICKUP_LIFETIME, TimerMode::Once),
+            collect_delay: Timer::from_seconds(collect_delay, TimerMode::Once),
+        }
+    }
+
+    fn collectable(&self) -> bool {
+        self.collect_delay.finished()
+    }
+}
+
+#[derive(Resource)]
+struct PickupData {
+    mesh: Handle<Mesh>,
+    materials: HashMap<ItemId, Handle<StandardMaterial>>,
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    registry: Res<ItemRegistry>,
+) {
+    let pickup_data = PickupData {
+        mesh: meshes.add(Cuboid::from_size(Vec3::ONE * 0.25)),
+        materials: ItemId::ALL
+            .iter()
+            .map(|item| {
+                let color = registry.get(*item).color;
+                (*item, materials.add(StandardMaterial::from_color(color)))
+            })
+            .collect(),
+    };
+
+    commands.insert_resource(pickup_data);
+}
+
+fn spawn_pickup_entity(
+    commands: &mut Commands,
+    pickup_data: &PickupData,
+    pickup: Pickup,
+    position: Vec3,
+) {
+    commands
+        .spawn(PbrBundle {
+            mesh: pickup_data.mesh.clone(),
+            material: pickup_data.materials[&pickup.stack.item].clone(),
+            transform: Transform::from_translation(position),
+            ..Default::default()
+        })
+        .insert((Collider::ball(0.25), Sensor))
+        .insert(pickup);
+}
+
+fn spawn_pickup(
+    mut commands: Commands,
+    mut spawn_events: EventReader<SpawnPickupEvent>,
+    pickup_data: Res<PickupData>,
+) {
+    for spawn_event in spawn_events.read() {
+        spawn_pickup_entity(
+            &mut commands,
+            &pickup_data,
+            Pickup::new(spawn_event.stack, 0.0),
+            spawn_event.position,
+        );
+    }
+}
+
+fn drop_items(
+    mut commands: Commands,
+    actors: Query<(&Transform, Option<&Facing>)>,
+    mut drop_events: EventReader<DropItemEvent>,
+    pickup_data: Res<PickupData>,
+) {
+    for drop_event in drop_events.read() {
+        let Ok((actor_transform, facing)) = actors.get(drop_event.actor) else {
+            continue;
+        };
+
+        let direction = facing.map_or(Vec3::X, |facing| facing.0);
+        let position = (actor_transform.translation + direction * 1.0).with_y(0.25);
+
+        // The delay keeps the dropped stack from being collected again straight away
+        spawn_pickup_entity(
+            &mut commands,
+            &pickup_data,
+            Pickup::new(drop_event.stack, DROP_COLLECT_DELAY),
+            position,
+        );
+    }
+}
+
+fn update_pickup_timers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pickups: Query<(Entity, &mut Pickup)>,
+) {
+    let dt = time.delta();
+    for (entity, mut pickup) in &mut pickups {
+        pickup.collect_delay.tick(dt);
+        pickup.lifetime.tick(dt);
+
+        if pickup.lifetime.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn merge_pickups(
+    mut commands: Commands,
+    mut pickups: Query<(Entity, &Transform, &mut Pickup)>,
+    registry: Res<ItemRegistry>,
+) {
+    let mut positions: Vec<(Entity, Vec3)> = pickups
+        .iter()
+        .map(|(entity, transform, _)| (entity, transform.translation))
+        .collect();
+
+    while let Some((entity, position)) = positions.pop() {
+        for (other_entity, other_position) in &positions {
+            if position.distance(*other_position) > MERGE_RADIUS {
+                continue;
+            }
+
+            let Ok([(_, _, mut pickup), (_, _, mut other)]) =
+                pickups.get_many_mut([entity, *other_entity])
+            else {
+                continue;
+            };
+
+            if !other.stack.stacks_with(&pickup.stack) || other.stack.count == 0 {
+                continue;
+            }
+
+            let max_stack = registry.max_stack(pickup.stack.item);
+            let moved = u32::min(
+                max_stack.saturating_sub(other.stack.count),
+                pickup.stack.count,
+            );
+            if moved == 0 {
+                continue;
+            }
+
+            other.stack.count += moved;
+            other.lifetime.reset();
+            pickup.stack.count -= moved;
+
+            if pickup.stack.count == 0 {
+                commands.entity(entity).despawn();
+                break;
+            }
+        }
+    }
+}
+
+fn magnetise_pickups(
+    time: Res<Time>,
+    player: Query<(&Transform, &Inventory), With<Player>>,
+    mut pickups: Query<(&mut Transform, &Pickup), Without<Player>>,
+    registry: Res<ItemRegistry>,
+) {
+    let Ok((player_transform, inventory)) = player.get_single() else {
+        return;
+    };
+
+    let target = player_transform.translation;
+    let dt = time.delta_seconds();
+
+    for (mut pickup_transform, pickup) in &mut pickups {
+        if !pickup.collectable() || inventory.space_for(&registry, pickup.stack.item) == 0 {
+            continue;
+        }
+
+        let to_player = (target - pickup_transform.translation).with_y(0.0);
+        let distance = to_player.length();
+        if distance > MAGNET_RADIUS || distance <= f32::EPSILON {
+            continue;
+        }
+
+        // Pull harder the closer the pickup gets
+        let speed = MAGNET_SPEED * (1.0 - distance / MAGNET_RADIUS + 0.25);
+        pickup_transform.translation += to_player.normalize() * f32::min(speed * dt, distance);
+    }
+}
+
+fn collect_within(
+    commands: &mut Commands,
+    radius: f32,
+    player: &mut Query<(&Transform, &mut Inventory), With<Player>>,
+    pickups: &mut Query<(Entity, &Transform, &mut Pickup), Without<Player>>,
+    registry: &ItemRegistry,
+) {
+    let Ok((player_transform, mut inventory)) = player.get_single_mut() else {
+        return;
+    };
+
+    for (pickup_entity, pickup_transform, mut pickup) in pickups.iter_mut() {
+        if !pickup.collectable() || pickup.stack.count == 0 {
+            continue;
+        }
+
+        let distance = player_transform
+            .translation
+            .with_y(0.0)
+            .distance(pickup_transform.translation.with_y(0.0));
+        if distance > radius {
+            continue;
+        }
+
+        pickup.stack.count = inventory.add_stack(registry, pickup.stack);
+        if pickup.stack.count == 0 {
+            commands.entity(pickup_entity).despawn();
+        }
+    }
+}
+
+fn collect_pickups(
+    mut commands: Commands,
+    mut player: Query<(&Transform, &mut Inventory), With<Player>>,
+    mut pickups: Query<(Entity, &Transform, &mut Pickup), Without<Player>>,
+    registry: Res<ItemRegistry>,
+) {
+    collect_within(
+        &mut commands,
+        COLLECT_RADIUS,
+        &mut player,
+        &mut pickups,
+        &registry,
+    );
+}
+
+fn collect_on_interact(
+    mut commands: Commands,
+    input: InputParam,
+    mut player: Query<(&Transform, &mut Inventory), With<Player>>,
+    mut pickups: Query<(Entity, &Transform, &mut Pickup), Without<Player>>,
+    registry: Res<ItemRegistry>,
+) {
+    if !input.action_just_pressed(PlayerAction::Interact) {
+        return;
+    }
+
+    collect_within(
+        &mut commands,
+        INTERACT_COLLECT_RADIUS,
+        &mut player,
+        &mut pickups,
+        &registry,
+    );
+}
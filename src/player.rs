@@ -1,60 +1,102 @@
-use bevy::{color::palettes, math::VectorSpace, prelude::*};
+use bevy::{color::palettes, prelude::*};
 use bevy_rapier3d::prelude::*;
 
 use crate::{
     build::BuildPlugin,
+    crafting::CraftingQueue,
+    faction::Faction,
+    health::Invulnerable,
     hitbox::{Hitbox, SpawnHitboxEvent, Target},
+    hotbar::{Hotbar, HotbarPlugin},
+    interaction::{
+        Highlighted, Interactable, InteractableEntities, InteractionEvent, InteractionKind,
+        InteractionProgress,
+    },
+    inventory::Inventory,
+    item::{ItemId, ItemRegistry},
+    movement::{character_controller, Movement, MovementPlugin, MovementSet},
     player_input::{InputMap, InputParam, PlayerAction},
+    stamina::{Stamina, StaminaPlugin},
+    targeting::{InteractionFocus, TargetingPlugin},
+    tool::{EquippedTool, ToolKind, ToolUsedEvent},
 };
 
+const INTERACT_RANGE: f32 = 1.5;
+const PLAYER_SPEED: f32 = 2.5;
+const PLAYER_STAMINA: f32 = 100.0;
+const SPRINT_MULTIPLIER: f32 = 1.7;
+// Stamina per second spent sprinting
+const SPRINT_STAMINA_COST: f32 = 20.0;
+const DODGE_MULTIPLIER: f32 = 3.5;
+const DODGE_DURATION: f32 = 0.3;
+const DODGE_STAMINA_COST: f32 = 25.0;
+const ATTACK_STAMINA_COST: f32 = 10.0;
+const INVENTORY_SIZE: usize = 24;
+
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(BuildPlugin)
-            .add_event::<InteractionEvent>()
-            .insert_state(PlayerState::Normal)
-            .init_resource::<InteractableEntities>()
-            .init_resource::<InputMap>()
-            .insert_resource(CameraZoom(0.0))
-            .add_systems(Startup, setup)
-            .add_systems(
-                FixedUpdate,
-                (
-                    update_camera,
-                    update_interactables.run_if(on_event::<CollisionEvent>()),
-                    highlight_interactables,
-                ),
-            )
-            .add_systems(
-                Update,
+        app.add_plugins((
+            BuildPlugin,
+            TargetingPlugin,
+            HotbarPlugin,
+            MovementPlugin,
+            StaminaPlugin,
+        ))
+        .insert_state(PlayerState::Normal)
+        .init_resource::<InputMap>()
+        .insert_resource(CameraZoom(0.0))
+        .add_systems(Startup, setup)
+        .add_systems(OnExit(PlayerState::Normal), stop_player)
+        .add_systems(
+            FixedUpdate,
+            (
+                update_interactables.run_if(on_event::<CollisionEvent>()),
+                highlight_interactables,
+            ),
+        )
+        .add_systems(
+            Update,
+            (
                 (
+                    dodge.run_if(
+                        in_state(PlayerState::Normal).and_then(not(any_with_component::<Dodging>)),
+                    ),
                     move_player.run_if(in_state(PlayerState::Normal)),
-                    interact,
-                    start_building.run_if(in_state(PlayerState::Normal)),
-                    cancel_building_mode.run_if(in_state(PlayerState::BuildingMode)),
-                    attack.run_if(in_state(PlayerState::Normal)),
+                    end_dodge,
+                )
+                    .chain()
+                    .before(MovementSet),
+                interact.run_if(
+                    not(in_state(PlayerState::Inventory))
+                        .and_then(not(in_state(PlayerState::Crafting)))
+                        .and_then(not(in_state(PlayerState::Blueprint))),
                 ),
-            );
+                cancel_interaction,
+                start_building.run_if(in_state(PlayerState::Normal)),
+                cancel_building_mode.run_if(in_state(PlayerState::BuildingMode)),
+                attack.run_if(in_state(PlayerState::Normal)),
+            ),
+        )
+        // Follows the player once physics has moved it this frame
+        .add_systems(
+            PostUpdate,
+            update_camera
+                .after(PhysicsSet::Writeback)
+                .before(TransformSystem::TransformPropagate),
+        );
     }
 }
 
-#[derive(Event)]
-pub struct InteractionEvent {
-    pub entity_1: Entity,
-    pub entity_2: Entity,
-}
-
 #[derive(States, PartialEq, Eq, Hash, Debug, Clone)]
 pub enum PlayerState {
     Normal,
     BuildingMode,
-}
-
-#[derive(Resource, Default)]
-struct InteractableEntities {
-    interactables: Vec<Entity>,
-    removed: Vec<Entity>,
+    Inventory,
+    Crafting,
+    Deconstructing,
+    Blueprint,
 }
 
 #[derive(Resource)]
@@ -64,16 +106,25 @@ struct PlayerData {
 }
 
 #[derive(Component)]
-pub struct Interactable;
+pub struct Player;
 
 #[derive(Component)]
-pub struct Player;
+pub struct Facing(pub Vec3);
+
+#[derive(Component)]
+pub struct PlayerSensor;
 
 fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    registry: Res<ItemRegistry>,
 ) {
+    let mut inventory = Inventory::new(INVENTORY_SIZE);
+    for tool in [ItemId::Axe, ItemId::Pickaxe, ItemId::Sword, ItemId::Hammer] {
+        inventory.add(&registry, tool, 1);
+    }
+
     let player_data = PlayerData {
         mesh: meshes.add(Cuboid::from_size(Vec3 {
             x: 0.5,
@@ -92,20 +143,38 @@ fn setup(
             ..Default::default()
         })
         .insert((
-            RigidBody::Dynamic,
+            RigidBody::KinematicPositionBased,
             Collider::capsule_y(0.5, 0.3),
-            Velocity::default(),
-            LockedAxes::ROTATION_LOCKED,
+            character_controller(),
+            Movement::new(PLAYER_SPEED),
+            ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_STATIC,
+        ))
+        .insert((
+            Player,
+            Faction::Player,
+            Facing(Vec3::X),
+            EquippedTool::default(),
+            Hotbar::default(),
+        ))
+        .insert((
+            inventory,
+            CraftingQueue::default(),
+            Stamina::new_full(PLAYER_STAMINA),
+            Name::new("Player"),
         ))
-        .insert(Player)
         .with_children(|builder| {
             builder
-                .spawn(Collider::ball(0.2))
+                .spawn(Collider::ball(INTERACT_RANGE))
                 .insert(Sensor)
                 .insert(ActiveEvents::COLLISION_EVENTS)
-                .insert(TransformBundle::from_transform(Transform::from_xyz(
-                    2.0, 0.0, 0.0,
-                )));
+                // Kinematic bodies only report fixed and kinematic colliders when asked to
+                .insert(
+                    ActiveCollisionTypes::default()
+                        | ActiveCollisionTypes::KINEMATIC_STATIC
+                        | ActiveCollisionTypes::KINEMATIC_KINEMATIC,
+                )
+                .insert(TransformBundle::default())
+                .insert(PlayerSensor);
         });
 
     commands.insert_resource(player_data);
@@ -114,11 +183,15 @@ fn setup(
 fn move_player(
     time: Res<Time>,
     input: Res<ButtonInput<KeyCode>>,
-    mut player: Query<&mut Velocity, With<Player>>,
-    mut player_sensor: Query<&mut Transform, With<Parent>>,
+    actions: InputParam,
+    mut player: Query<(&mut Movement, &mut Facing, &mut Stamina, Has<Dodging>), With<Player>>,
 ) {
-    let mut player_velocity = player.single_mut();
-    let dt = time.delta_seconds();
+    let (mut movement, mut facing, mut stamina, dodging) = player.single_mut();
+
+    // Dodging keeps its own course until it ends
+    if dodging {
+        return;
+    }
 
     let mut velocity = Vec3::ZERO;
     if input.pressed(KeyCode::KeyW) {
@@ -136,12 +209,70 @@ fn move_player(
 
     if velocity != Vec3::ZERO {
         velocity = velocity.normalize();
-        let mut player_sensor = player_sensor.single_mut();
-        player_sensor.translation = velocity * 0.5;
+        facing.0 = velocity;
+    }
+
+    let sprinting = actions.action_pressed(PlayerAction::Sprint)
+        && velocity != Vec3::ZERO
+        && stamina.drain(SPRINT_STAMINA_COST * time.delta_seconds());
+
+    movement.direction = velocity;
+    movement.speed_multiplier = if sprinting { SPRINT_MULTIPLIER } else { 1.0 };
+}
+
+#[derive(Component)]
+struct Dodging(Timer);
+
+// Rolls the way the player faces, unharmed until the roll ends
+fn dodge(
+    mut commands: Commands,
+    input: InputParam,
+    mut player: Query<(Entity, &Facing, &mut Movement, &mut Stamina), With<Player>>,
+) {
+    if !input.action_just_pressed(PlayerAction::Dodge) {
+        return;
+    }
+
+    let (player_entity, facing, mut movement, mut stamina) = player.single_mut();
+
+    if !stamina.try_spend(DODGE_STAMINA_COST) {
+        info!("Too tired to dodge");
+        return;
+    }
+
+    movement.direction = facing.0;
+    movement.speed_multiplier = DODGE_MULTIPLIER;
+    let velocity = facing.0 * movement.speed * DODGE_MULTIPLIER;
+    movement.set_velocity(velocity);
+
+    commands.entity(player_entity).insert((
+        Dodging(Timer::from_seconds(DODGE_DURATION, TimerMode::Once)),
+        Invulnerable,
+    ));
+}
+
+fn end_dodge(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player: Query<(Entity, &mut Dodging, &mut Movement), With<Player>>,
+) {
+    let Ok((player_entity, mut dodging, mut movement)) = player.get_single_mut() else {
+        return;
+    };
+
+    dodging.0.tick(time.delta());
+    if !dodging.0.finished() {
+        return;
     }
 
-    let player_speed = 150.0;
-    player_velocity.linvel = velocity * dt * player_speed;
+    movement.speed_multiplier = 1.0;
+    commands
+        .entity(player_entity)
+        .remove::<(Dodging, Invulnerable)>();
+}
+
+fn stop_player(mut player: Query<&mut Movement, With<Player>>) {
+    player.single_mut().direction = Vec3::ZERO;
 }
 
 #[derive(Resource)]
@@ -167,15 +298,12 @@ fn update_camera(
 }
 
 fn update_interactables(
-    player: Query<&Children, With<Player>>,
-    sensor: Query<Entity, With<Parent>>,
+    sensor: Query<Entity, With<PlayerSensor>>,
     mut interactables: Query<Entity, With<Interactable>>,
     mut collision_events: EventReader<CollisionEvent>,
     mut interactable_entities: ResMut<InteractableEntities>,
 ) {
-    let player_children = player.single();
-    let sensor_entity = player_children[0];
-    assert_eq!(sensor_entity, sensor.single());
+    let sensor_entity = sensor.single();
 
     for collision_event in collision_events.read() {
         match collision_event {
@@ -202,8 +330,7 @@ fn update_interactables(
                         .iter()
                         .position(|entity| entity == entity_1 || entity == entity_2)
                     {
-                        let removed = interactable_entities.interactables.swap_remove(index);
-                        interactable_entities.removed.push(removed);
+                        interactable_entities.interactables.swap_remove(index);
                     }
                 }
             }
@@ -212,44 +339,92 @@ fn update_interactables(
 }
 
 fn highlight_interactables(
-    mut interactable_entities: ResMut<InteractableEntities>,
-    interactables: Query<&Handle<StandardMaterial>, With<Interactable>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+    focus: Res<InteractionFocus>,
+    highlighted: Query<Entity, With<Highlighted>>,
+    interactables: Query<Entity, (With<Interactable>, Without<Highlighted>)>,
 ) {
-    for entity in &interactable_entities.interactables {
-        if let Ok(handle) = interactables.get(*entity) {
-            let material = materials.get_mut(handle).unwrap();
-            material.emissive = LinearRgba::rgb(0.0, 0.3, 0.0);
+    for entity in &highlighted {
+        if focus.target != Some(entity) {
+            commands.entity(entity).remove::<Highlighted>();
         }
     }
 
-    for removed_entity in &interactable_entities.removed {
-        if let Ok(handle) = interactables.get(*removed_entity) {
-            let material = materials.get_mut(handle).unwrap();
-            material.emissive = LinearRgba::ZERO;
-        }
+    if let Some(entity) = focus
+        .target
+        .and_then(|entity| interactables.get(entity).ok())
+    {
+        commands.entity(entity).try_insert(Highlighted);
     }
-
-    interactable_entities.removed.clear();
 }
 
 fn interact(
-    player: Query<Entity, With<Player>>,
+    mut commands: Commands,
+    player: Query<(Entity, &EquippedTool), With<Player>>,
     input: InputParam,
-    interactable_entities: Res<InteractableEntities>,
+    interactables: Query<&Interactable>,
+    focus: Res<InteractionFocus>,
     mut interaction_event: EventWriter<InteractionEvent>,
 ) {
     if !input.action_just_pressed(PlayerAction::Interact) {
         return;
     }
 
-    let player_entity = player.single();
+    let (player_entity, equipped_tool) = player.single();
 
-    if let Some(entity) = interactable_entities.interactables.first() {
-        interaction_event.send(InteractionEvent {
-            entity_1: player_entity,
-            entity_2: *entity,
-        });
+    let Some(entity) = focus.target else {
+        return;
+    };
+
+    let Ok(interactable) = interactables.get(entity) else {
+        return;
+    };
+
+    // Holding a hammer turns any interaction into a repair where possible
+    let repairing = equipped_tool.kind() == Some(ToolKind::Hammer)
+        && interactable.accepts(InteractionKind::Repair);
+    let Some(kind) = repairing
+        .then_some(InteractionKind::Repair)
+        .or(interactable.primary_kind())
+    else {
+        return;
+    };
+
+    if interactable.duration > 0.0 {
+        commands
+            .entity(player_entity)
+            .insert(InteractionProgress::new(
+                entity,
+                kind,
+                interactable.duration,
+            ));
+        return;
+    }
+
+    interaction_event.send(InteractionEvent {
+        actor: player_entity,
+        target: entity,
+        kind,
+    });
+}
+
+fn cancel_interaction(
+    mut commands: Commands,
+    input: InputParam,
+    player: Query<(Entity, &InteractionProgress), With<Player>>,
+    focus: Res<InteractionFocus>,
+) {
+    let Ok((player_entity, progress)) = player.get_single() else {
+        return;
+    };
+
+    let released = !input.action_pressed(PlayerAction::Interact);
+    let moved_away = focus.target != Some(progress.target);
+
+    if released || moved_away {
+        commands
+            .entity(player_entity)
+            .remove::<InteractionProgress>();
     }
 }
 
@@ -274,22 +449,32 @@ pub struct AttackEvent(pub Entity);
 
 fn attack(
     input: InputParam,
-    player: Query<Entity, With<Player>>,
-    sensor: Query<&GlobalTransform, With<Parent>>,
+    mut player: Query<(Entity, &Transform, &Facing, &EquippedTool, &mut Stamina), With<Player>>,
     mut attack_event: EventWriter<SpawnHitboxEvent>,
+    mut tool_used_event: EventWriter<ToolUsedEvent>,
 ) {
     if !input.action_just_pressed(PlayerAction::Attack) {
         return;
     }
 
-    let player = player.single();
-    let position = sensor.single().translation();
+    let (player, player_transform, facing, equipped_tool, mut stamina) = player.single_mut();
+    if !stamina.try_spend(ATTACK_STAMINA_COST) {
+        info!("Too tired to attack");
+        return;
+    }
+    let half_extents = equipped_tool.hitbox_half_extents();
+    let position = player_transform.translation + facing.0 * (0.3 + half_extents.x);
 
     attack_event.send(SpawnHitboxEvent(Hitbox {
         sender: player,
-        collider: Collider::cuboid(0.2, 0.5, 0.2),
+        collider: Collider::cuboid(half_extents.x, half_extents.y, half_extents.z),
         position,
         target: Target::Enemies,
+        damage: equipped_tool.attack_damage(),
         lifetime: Timer::from_seconds(0.5, TimerMode::Once),
     }));
+
+    if equipped_tool.0.is_some() {
+        tool_used_event.send(ToolUsedEvent { actor: player });
+    }
 }
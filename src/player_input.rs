@@ -13,17 +13,58 @@ impl Default for InputMap {
                 (PlayerAction::Attack, KeyCode::Space),
                 (PlayerAction::Build, KeyCode::KeyB),
                 (PlayerAction::Cancel, KeyCode::KeyC),
+                (PlayerAction::CycleTarget, KeyCode::Tab),
+                (PlayerAction::Inventory, KeyCode::KeyI),
+                (PlayerAction::Drop, KeyCode::KeyG),
+                (PlayerAction::Crafting, KeyCode::KeyK),
+                (PlayerAction::NextBuilding, KeyCode::KeyE),
+                (PlayerAction::RotateBuilding, KeyCode::KeyR),
+                (PlayerAction::Deconstruct, KeyCode::KeyX),
+                (PlayerAction::Blueprint, KeyCode::KeyV),
+                (PlayerAction::Undo, KeyCode::KeyZ),
+                (PlayerAction::Redo, KeyCode::KeyY),
+                (PlayerAction::Sprint, KeyCode::ShiftLeft),
+                (PlayerAction::Dodge, KeyCode::KeyQ),
+                (PlayerAction::HotbarSlot(0), KeyCode::Digit1),
+                (PlayerAction::HotbarSlot(1), KeyCode::Digit2),
+                (PlayerAction::HotbarSlot(2), KeyCode::Digit3),
+                (PlayerAction::HotbarSlot(3), KeyCode::Digit4),
+                (PlayerAction::HotbarSlot(4), KeyCode::Digit5),
+                (PlayerAction::HotbarSlot(5), KeyCode::Digit6),
             ]),
         }
     }
 }
 
+impl InputMap {
+    // Name of the key bound to an action, as shown in the UI
+    pub fn key_label(&self, player_action: PlayerAction) -> String {
+        let Some(key_code) = self.map.get(&player_action) else {return "?".to_string();};
+
+        let label = format!("{:?}", key_code);
+        label.trim_start_matches("Key").trim_start_matches("Digit").to_string()
+    }
+}
+
 #[derive(PartialEq, Eq, Hash)]
 pub enum PlayerAction {
     Interact,
     Attack,
     Build,
     Cancel,
+    CycleTarget,
+    Inventory,
+    Drop,
+    Crafting,
+    NextBuilding,
+    RotateBuilding,
+    Deconstruct,
+    Blueprint,
+    Undo,
+    Redo,
+    Sprint,
+    Dodge,
+    HotbarSlot(usize),
 }
 
 #[derive(SystemParam)]
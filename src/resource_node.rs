@@ -0,0 +1,324 @@
+use std::f32::consts::PI;
+
+use bevy::{color::palettes, prelude::*};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    health::Health,
+    interaction::{Interactable, InteractionEvent, InteractionKind},
+    item::{ItemId, ItemStack},
+    pickup::SpawnPickupEvent,
+    stamina::Stamina,
+    tool::{EquippedTool, ToolKind, ToolUsedEvent},
+};
+
+const HARVEST_STAMINA_COST: f32 = 6.0;
+
+pub struct ResourceNodePlugin;
+
+impl Plugin for ResourceNodePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<NodeHitEvent>()
+            .add_event::<NodeDepletedEvent>()
+            .add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (
+                    handle_harvest_interaction.run_if(on_event::<InteractionEvent>()),
+                    despawn_depleted_nodes.run_if(on_event::<NodeDepletedEvent>()),
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Component)]
+pub struct ResourceNode {
+    pub yield_per_hit: Vec<(ItemId, u32)>,
+    pub yield_on_depleted: Vec<(ItemId, u32)>,
+    pub damage_per_hit: i32,
+    pub required_tool: Option<ToolKind>,
+    // Tool whose harvest damage replaces `damage_per_hit`
+    pub effective_tool: Option<ToolKind>,
+    pub depletion: Depletion,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Depletion {
+    Despawn,
+    Fell,
+}
+
+#[derive(Event)]
+pub struct NodeHitEvent {
+    pub node: Entity,
+    pub actor: Entity,
+}
+
+#[derive(Event)]
+pub struct NodeDepletedEvent {
+    pub node: Entity,
+    pub actor: Entity,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NodeKind {
+    Rock,
+    Bush,
+    OreVein,
+}
+
+impl NodeKind {
+    fn health(&self) -> i32 {
+        match self {
+            NodeKind::Rock => 12,
+            NodeKind::Bush => 3,
+            NodeKind::OreVein => 20,
+        }
+    }
+
+    fn resource_node(&self) -> ResourceNode {
+        match self {
+            NodeKind::Rock => ResourceNode {
+                yield_per_hit: vec![(ItemId::Stone, 2)],
+                yield_on_depleted: vec![(ItemId::Stone, 4)],
+                damage_per_hit: 3,
+                required_tool: Some(ToolKind::Pickaxe),
+                effective_tool: Some(ToolKind::Pickaxe),
+                depletion: Depletion::Despawn,
+            },
+            NodeKind::Bush => ResourceNode {
+                yield_per_hit: vec![(ItemId::Berries, 1)],
+                yield_on_depleted: vec![(ItemId::Berries, 2), (ItemId::Fibre, 2)],
+                damage_per_hit: 1,
+                // Picked by hand on purpose, so berries and fibre need no tools
+                required_tool: None,
+                effective_tool: None,
+                depletion: Depletion::Despawn,
+            },
+            NodeKind::OreVein => ResourceNode {
+                yield_per_hit: vec![(ItemId::Ore, 1)],
+                yield_on_depleted: vec![(ItemId::Ore, 3), (ItemId::Stone, 2)],
+                damage_per_hit: 2,
+                required_tool: Some(ToolKind::Pickaxe),
+                effective_tool: Some(ToolKind::Pickaxe),
+                depletion: Depletion::Despawn,
+            },
+        }
+    }
+
+    fn mesh(&self) -> Mesh {
+        match self {
+            NodeKind::Rock => Cuboid::from_size(Vec3::new(1.0, 0.6, 0.8)).into(),
+            NodeKind::Bush => Sphere::new(0.4).into(),
+            NodeKind::OreVein => Cuboid::from_size(Vec3::new(1.2, 0.8, 1.2)).into(),
+        }
+    }
+
+    fn collider(&self) -> Collider {
+        match self {
+            NodeKind::Rock => Collider::cuboid(0.5, 0.3, 0.4),
+            NodeKind::Bush => Collider::ball(0.4),
+            NodeKind::OreVein => Collider::cuboid(0.6, 0.4, 0.6),
+        }
+    }
+
+    fn color(&self) -> Srgba {
+        match self {
+            NodeKind::Rock => palettes::basic::GRAY,
+            NodeKind::Bush => palettes::css::DARK_OLIVEGREEN,
+            NodeKind::OreVein => palettes::css::SIENNA,
+        }
+    }
+
+    fn prompt(&self) -> &'static str {
+        match self {
+            NodeKind::Rock => "Mine rock",
+            NodeKind::Bush => "Gather",
+            NodeKind::OreVein => "Mine ore",
+        }
+    }
+
+    fn interaction_duration(&self) -> f32 {
+        match self {
+            NodeKind::Rock => 0.8,
+            NodeKind::Bush => 0.4,
+            NodeKind::OreVein => 1.0,
+        }
+    }
+
+    fn height(&self) -> f32 {
+        match self {
+            NodeKind::Rock => 0.3,
+            NodeKind::Bush => 0.4,
+            NodeKind::OreVein => 0.4,
+        }
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    spawn_start_nodes(&mut commands, &mut meshes, &mut materials);
+}
+
+fn spawn_start_nodes(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+) {
+    let start_nodes = [
+        (NodeKind::Rock, 6, 14.0),
+        (NodeKind::Bush, 8, 8.0),
+        (NodeKind::OreVein, 3, 20.0),
+    ];
+
+    for (kind, nb_nodes, base_dist) in start_nodes {
+        for i in 0..nb_nodes {
+            let step = (i as f32 / nb_nodes as f32) * PI * 2.0 + rand::random::<f32>();
+            let dist = rand::random::<f32>() * 4.0 + base_dist;
+            let x = f32::cos(step) * dist;
+            let y = kind.height();
+            let z = f32::sin(step) * dist;
+            let location = Vec3 { x, y, z };
+
+            spawn_node(commands, kind, location, meshes, materials);
+        }
+    }
+}
+
+pub fn spawn_node(
+    commands: &mut Commands,
+    kind: NodeKind,
+    location: Vec3,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+) {
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(kind.mesh()),
+            material: materials.add(StandardMaterial::from_color(kind.color())),
+            transform: Transform::from_translation(location),
+            ..Default::default()
+        })
+        .insert((RigidBody::Fixed, kind.collider()))
+        .insert(Health::new_full(kind.health()))
+        .insert(kind.resource_node())
+        .insert(
+            Interactable::new(InteractionKind::Harvest, kind.prompt())
+                .with_duration(kind.interaction_duration()),
+        );
+}
+
+fn handle_harvest_interaction(
+    mut nodes: Query<(&mut Health, &ResourceNode, &Transform)>,
+    mut actors: Query<(Option<&EquippedTool>, &Transform, Option<&mut Stamina>)>,
+    mut interaction_events: EventReader<InteractionEvent>,
+    mut hit_events: EventWriter<NodeHitEvent>,
+    mut depleted_events: EventWriter<NodeDepletedEvent>,
+    mut spawn_pickup_event: EventWriter<SpawnPickupEvent>,
+    mut tool_used_event: EventWriter<ToolUsedEvent>,
+) {
+    for interacion_event in interaction_events.read() {
+        if interacion_event.kind != InteractionKind::Harvest {
+            continue;
+        }
+
+        let Ok((mut node_health, node, node_transform)) = nodes.get_mut(interacion_event.target)
+        else {
+            continue;
+        };
+
+        let Ok((tool, actor_transform, stamina)) = actors.get_mut(interacion_event.actor) else {
+            continue;
+        };
+
+        let has_tool = tool.map_or(node.required_tool.is_none(), |tool| {
+            tool.satisfies(node.required_tool)
+        });
+        if !has_tool {
+            info!("{:?} required to harvest this", node.required_tool);
+            continue;
+        }
+
+        if node_health.is_dead() {
+            continue;
+        }
+
+        if stamina.is_some_and(|mut stamina| !stamina.try_spend(HARVEST_STAMINA_COST)) {
+            info!("Too tired to harvest");
+            continue;
+        }
+
+        let effective_tool = tool
+            .and_then(|tool| tool.0)
+            .filter(|tool| Some(tool.kind) == node.effective_tool);
+
+        let damage = effective_tool.map_or(node.damage_per_hit, |tool| tool.harvest_damage);
+        node_health.change(-damage);
+
+        if effective_tool.is_some() {
+            tool_used_event.send(ToolUsedEvent {
+                actor: interacion_event.actor,
+            });
+        }
+
+        let mut yields = node.yield_per_hit.clone();
+        if node_health.is_dead() {
+            yields.extend(node.yield_on_depleted.iter().copied());
+        }
+
+        // Yields pop out of the node on the actor's side
+        let to_actor = (actor_transform.translation - node_transform.translation)
+            .with_y(0.0)
+            .normalize_or_zero();
+        for (item, amount) in yields {
+            let jitter = Vec3::new(
+                rand::random::<f32>() - 0.5,
+                0.0,
+                rand::random::<f32>() - 0.5,
+            );
+            spawn_pickup_event.send(SpawnPickupEvent {
+                stack: ItemStack::new(item, amount),
+                position: (node_transform.translation + to_actor * 0.8 + jitter * 0.4).with_y(0.25),
+            });
+        }
+
+        let node_entity = interacion_event.target;
+        let actor = interacion_event.actor;
+
+        if node_health.is_dead() {
+            depleted_events.send(NodeDepletedEvent {
+                node: node_entity,
+                actor,
+            });
+        } else {
+            hit_events.send(NodeHitEvent {
+                node: node_entity,
+                actor,
+            });
+        }
+    }
+}
+
+fn despawn_depleted_nodes(
+    mut commands: Commands,
+    nodes: Query<(&ResourceNode, &Handle<Mesh>, &Handle<StandardMaterial>)>,
+    mut depleted_events: EventReader<NodeDepletedEvent>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for depleted_event in depleted_events.read() {
+        let Ok((node, mesh_handle, material_handle)) = nodes.get(depleted_event.node) else {
+            continue;
+        };
+
+        if node.depletion == Depletion::Despawn {
+            commands.entity(depleted_event.node).despawn_recursive();
+            meshes.remove(mesh_handle);
+            materials.remove(material_handle);
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use bevy::{color::palettes, prelude::*};
+
+use crate::player::Player;
+
+// Seconds without spending before stamina starts coming back
+const REST_DELAY: f32 = 1.0;
+const REGEN_RATE: f32 = 25.0;
+const BAR_WIDTH: f32 = 160.0;
+
+pub struct StaminaPlugin;
+
+impl Plugin for StaminaPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_stamina_bar)
+            .add_systems(Update, (regenerate_stamina, update_stamina_bar).chain());
+    }
+}
+
+#[derive(Component)]
+pub struct Stamina {
+    max: f32,
+    current: f32,
+    rest: Timer,
+}
+
+impl Stamina {
+    pub fn new_full(max: f32) -> Self {
+        Self {
+            max,
+            current: max,
+            rest: Timer::from_seconds(REST_DELAY, TimerMode::Once),
+        }
+    }
+
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+
+    pub fn max(&self) -> f32 {
+        self.max
+    }
+
+    pub fn fraction(&self) -> f32 {
+        self.current / self.max
+    }
+
+    // Spends the whole amount, or nothing if there is not enough left
+    pub fn try_spend(&mut self, amount: f32) -> bool {
+        if self.current < amount {
+            return false;
+        }
+
+        self.current -= amount;
+        self.rest.reset();
+        true
+    }
+
+    // Spends what is left of the amount, for costs paid over time
+    pub fn drain(&mut self, amount: f32) -> bool {
+        if self.current <= 0.0 {
+            return false;
+        }
+
+        self.current = (self.current - amount).max(0.0);
+        self.rest.reset();
+        true
+    }
+}
+
+#[derive(Component)]
+struct StaminaBar;
+
+fn regenerate_stamina(time: Res<Time>, mut staminas: Query<&mut Stamina>) {
+    for mut stamina in &mut staminas {
+        stamina.rest.tick(time.delta());
+        if !stamina.rest.finished() || stamina.current >= stamina.max {
+            continue;
+        }
+
+        stamina.current = (stamina.current + REGEN_RATE * time.delta_seconds()).min(stamina.max);
+    }
+}
+
+fn spawn_stamina_bar(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(8.0),
+                right: Val::Px(8.0),
+                width: Val::Px(BAR_WIDTH),
+                height: Val::Px(10.0),
+                ..Default::default()
+            },
+            background_color: Color::srgba(0.15, 0.15, 0.15, 0.9).into(),
+            ..Default::default()
+        })
+        .with_children(|builder| {
+            builder.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..Default::default()
+                    },
+                    background_color: Color::from(palettes::css::GOLD).into(),
+                    ..Default::default()
+                },
+                StaminaBar,
+            ));
+        });
+}
+
+fn update_stamina_bar(
+    player: Query<&Stamina, (With<Player>, Changed<Stamina>)>,
+    mut bar: Query<&mut Style, With<StaminaBar>>,
+) {
+    let (Ok(stamina), Ok(mut style)) = (player.get_single(), bar.get_single_mut()) else {
+        return;
+    };
+
+    let width = Val::Percent(stamina.fraction() * 100.0);
+    if style.width != width {
+        style.width = width;
+    }
+}
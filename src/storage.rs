@@ -0,0 +1,91 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    ecs::component::{ComponentHooks, StorageType},
+    prelude::*,
+};
+
+use crate::{
+    interaction::{InteractionEvent, InteractionKind},
+    inventory::Inventory,
+    inventory_ui::OpenContainers,
+    pickup::SpawnPickupEvent,
+    player::{Player, PlayerState},
+};
+
+pub const CHEST_SIZE: usize = 18;
+
+pub struct StoragePlugin;
+
+impl Plugin for StoragePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            open_chests
+                .run_if(on_event::<InteractionEvent>())
+                .run_if(in_state(PlayerState::Normal)),
+        );
+    }
+}
+
+// Storage container whose inventory spills onto the ground when it goes away
+pub struct Chest;
+
+impl Component for Chest {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    // Runs on both removal and despawn, so contents are never lost with the chest
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks.on_remove(|mut world, entity, _| {
+            let Some(position) = world
+                .get::<GlobalTransform>(entity)
+                .map(GlobalTransform::translation)
+            else {
+                return;
+            };
+
+            let Some(mut inventory) = world.get_mut::<Inventory>(entity) else {
+                return;
+            };
+            let stacks: Vec<_> = (0..inventory.size())
+                .filter_map(|index| inventory.take_slot(index))
+                .collect();
+
+            let nb_stacks = stacks.len();
+            for (index, stack) in stacks.into_iter().enumerate() {
+                let angle = index as f32 / nb_stacks as f32 * TAU;
+                let offset = Vec3::new(f32::cos(angle), 0.0, f32::sin(angle)) * 0.8;
+
+                world.send_event(SpawnPickupEvent {
+                    stack,
+                    position: (position + offset).with_y(0.25),
+                });
+            }
+        });
+    }
+}
+
+fn open_chests(
+    player: Query<Entity, With<Player>>,
+    chests: Query<(), (With<Chest>, With<Inventory>)>,
+    mut interaction_events: EventReader<InteractionEvent>,
+    mut open_containers: ResMut<OpenContainers>,
+    mut next_player_state: ResMut<NextState<PlayerState>>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+
+    for interaction_event in interaction_events.read() {
+        if interaction_event.kind != InteractionKind::Open || interaction_event.actor != player {
+            continue;
+        }
+
+        if !chests.contains(interaction_event.target) {
+            continue;
+        }
+
+        open_containers.0 = vec![player, interaction_event.target];
+        next_player_state.set(PlayerState::Inventory);
+    }
+}
@@ -0,0 +1,156 @@
+use bevy::{color::palettes, prelude::*};
+
+use crate::{
+    interaction::{Interactable, InteractableEntities},
+    player::{Facing, Player},
+    player_input::{InputMap, InputParam, PlayerAction},
+};
+
+const FACING_WEIGHT: f32 = 1.5;
+
+pub struct TargetingPlugin;
+
+impl Plugin for TargetingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InteractionFocus>()
+            .add_systems(Startup, setup_ui)
+            .add_systems(Update, (rank_targets, cycle_target, update_ui).chain());
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct InteractionFocus {
+    pub target: Option<Entity>,
+    pub candidates: Vec<Entity>,
+    locked: bool,
+}
+
+#[derive(Component)]
+struct PromptText;
+
+fn target_score(player_position: Vec3, facing: Vec3, target_position: Vec3) -> f32 {
+    let to_target = target_position - player_position;
+    let to_target = Vec3::new(to_target.x, 0.0, to_target.z);
+    let angle = facing.angle_between(to_target.normalize_or_zero());
+
+    to_target.length() + angle * FACING_WEIGHT
+}
+
+fn rank_targets(
+    player: Query<(&Transform, &Facing), With<Player>>,
+    interactables: Query<&GlobalTransform, With<Interactable>>,
+    interactable_entities: Res<InteractableEntities>,
+    mut focus: ResMut<InteractionFocus>,
+) {
+    let (player_transform, facing) = player.single();
+
+    let mut candidates: Vec<(Entity, f32)> = interactable_entities
+        .interactables
+        .iter()
+        .filter_map(|entity| {
+            let target_transform = interactables.get(*entity).ok()?;
+            let score = target_score(
+                player_transform.translation,
+                facing.0,
+                target_transform.translation(),
+            );
+            Some((*entity, score))
+        })
+        .collect();
+    candidates.sort_by(|(_, score_1), (_, score_2)| score_1.total_cmp(score_2));
+    let candidates: Vec<Entity> = candidates.into_iter().map(|(entity, _)| entity).collect();
+
+    let keep_locked = focus.locked
+        && focus
+            .target
+            .is_some_and(|target| candidates.contains(&target));
+    let target = if keep_locked {
+        focus.target
+    } else {
+        candidates.first().copied()
+    };
+
+    // Only touch the resource when something changed so `is_changed` stays meaningful
+    if focus.candidates != candidates || focus.target != target || focus.locked != keep_locked {
+        focus.candidates = candidates;
+        focus.target = target;
+        focus.locked = keep_locked;
+    }
+}
+
+fn cycle_target(input: InputParam, mut focus: ResMut<InteractionFocus>) {
+    if !input.action_just_pressed(PlayerAction::CycleTarget) || focus.candidates.is_empty() {
+        return;
+    }
+
+    let next_index = match focus.target {
+        Some(target) => focus
+            .candidates
+            .iter()
+            .position(|candidate| *candidate == target)
+            .map_or(0, |index| (index + 1) % focus.candidates.len()),
+        None => 0,
+    };
+
+    focus.target = Some(focus.candidates[next_index]);
+    focus.locked = true;
+}
+
+fn setup_ui(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(90.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with_children(|builder| {
+            builder
+                .spawn(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 24.0,
+                        color: palettes::basic::WHITE.into(),
+                        ..Default::default()
+                    },
+                ))
+                .insert(PromptText);
+        });
+}
+
+fn update_ui(
+    focus: Res<InteractionFocus>,
+    input_map: Res<InputMap>,
+    interactables: Query<&Interactable>,
+    mut prompt_text: Query<&mut Text, With<PromptText>>,
+) {
+    let mut prompt_text = prompt_text.single_mut();
+
+    let prompt = focus
+        .target
+        .and_then(|target| interactables.get(target).ok())
+        .map(|interactable| {
+            let key = input_map.key_label(PlayerAction::Interact);
+
+            let mut prompt = format!("[{}] {}", key, interactable.prompt);
+            if focus.candidates.len() > 1 {
+                let index = focus
+                    .candidates
+                    .iter()
+                    .position(|candidate| Some(*candidate) == focus.target)
+                    .unwrap_or(0);
+                prompt += &format!("  ({}/{})", index + 1, focus.candidates.len());
+            }
+
+            prompt
+        })
+        .unwrap_or_default();
+
+    if prompt_text.sections[0].value != prompt {
+        prompt_text.sections[0].value = prompt;
+    }
+}
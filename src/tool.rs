@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+
+pub const FIST_DAMAGE: i32 = 1;
+pub const FIST_HITBOX: Vec3 = Vec3::new(0.2, 0.5, 0.2);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ToolKind {
+    Axe,
+    Pickaxe,
+    Sword,
+    Hammer,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ToolStats {
+    pub kind: ToolKind,
+    pub attack_damage: i32,
+    pub harvest_damage: i32,
+    pub hitbox_half_extents: Vec3,
+    pub max_durability: u32,
+}
+
+impl ToolStats {
+    pub fn new(
+        kind: ToolKind,
+        attack_damage: i32,
+        harvest_damage: i32,
+        max_durability: u32,
+    ) -> Self {
+        let hitbox_half_extents = match kind {
+            ToolKind::Sword => Vec3::new(0.5, 0.5, 0.5),
+            ToolKind::Axe | ToolKind::Hammer => Vec3::new(0.35, 0.5, 0.35),
+            ToolKind::Pickaxe => Vec3::new(0.3, 0.5, 0.3),
+        };
+
+        Self {
+            kind,
+            attack_damage,
+            harvest_damage,
+            hitbox_half_extents,
+            max_durability,
+        }
+    }
+}
+
+#[derive(Event)]
+pub struct ToolUsedEvent {
+    pub actor: Entity,
+}
+
+#[derive(Component, Default, PartialEq)]
+pub struct EquippedTool(pub Option<ToolStats>);
+
+impl EquippedTool {
+    pub fn kind(&self) -> Option<ToolKind> {
+        self.0.map(|tool| tool.kind)
+    }
+
+    pub fn satisfies(&self, required_tool: Option<ToolKind>) -> bool {
+        match required_tool {
+            Some(required_tool) => self.kind() == Some(required_tool),
+            None => true,
+        }
+    }
+
+    pub fn attack_damage(&self) -> i32 {
+        self.0.map_or(FIST_DAMAGE, |tool| tool.attack_damage)
+    }
+
+    pub fn hitbox_half_extents(&self) -> Vec3 {
+        self.0.map_or(FIST_HITBOX, |tool| tool.hitbox_half_extents)
+    }
+}
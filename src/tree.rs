@@ -5,10 +5,23 @@ use bevy_rapier3d::prelude::*;
 
 use crate::{
     health::Health,
-    inventory::Inventory,
-    player::{Interactable, InteractionEvent},
+    interaction::{Interactable, InteractionKind},
+    item::{ItemId, ItemStack},
+    pickup::SpawnPickupEvent,
+    player::Facing,
+    resource_node::{Depletion, NodeDepletedEvent, NodeHitEvent, ResourceNode},
+    tool::ToolKind,
 };
 
+const SHAKE_DURATION: f32 = 0.3;
+// World gravity is off, so felled trees are pulled down by hand
+const FALL_GRAVITY: f32 = 9.81;
+const TREE_MASS: f32 = 50.0;
+const FELL_TORQUE_IMPULSE: f32 = 40.0;
+// Felled trees are removed once they sleep, or after this many seconds
+const MAX_FALL_TIME: f32 = 4.0;
+const WOOD_PICKUPS_PER_TREE: u32 = 3;
+
 pub struct TreePlugin;
 
 impl Plugin for TreePlugin {
@@ -16,8 +29,10 @@ impl Plugin for TreePlugin {
         app.add_systems(Startup, setup).add_systems(
             Update,
             (
-                handle_tree_interaction.run_if(on_event::<InteractionEvent>()),
-                despawn_dead_trees,
+                shake_hit_trees.run_if(on_event::<NodeHitEvent>()),
+                start_felling.run_if(on_event::<NodeDepletedEvent>()),
+                update_tree_shake,
+                update_falling_trees,
             ),
         );
     }
@@ -26,6 +41,80 @@ impl Plugin for TreePlugin {
 #[derive(Component)]
 pub struct Tree;
 
+#[derive(Component)]
+struct TreeShake {
+    axis: Vec3,
+    timer: Timer,
+}
+
+#[derive(Component)]
+struct FallingTree {
+    half_height: f32,
+    timeout: Timer,
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TreeSpecies {
+    Pine,
+    Oak,
+    Birch,
+}
+
+impl TreeSpecies {
+    const ALL: [TreeSpecies; 3] = [TreeSpecies::Pine, TreeSpecies::Oak, TreeSpecies::Birch];
+
+    fn health(&self) -> i32 {
+        match self {
+            TreeSpecies::Pine => 10,
+            TreeSpecies::Oak => 18,
+            TreeSpecies::Birch => 6,
+        }
+    }
+
+    fn wood_per_hit(&self) -> u32 {
+        match self {
+            TreeSpecies::Pine => 1,
+            TreeSpecies::Oak => 2,
+            TreeSpecies::Birch => 1,
+        }
+    }
+
+    fn wood_when_felled(&self) -> u32 {
+        match self {
+            TreeSpecies::Pine => 9,
+            TreeSpecies::Oak => 15,
+            TreeSpecies::Birch => 6,
+        }
+    }
+
+    fn size(&self) -> Vec3 {
+        match self {
+            TreeSpecies::Pine => Vec3::new(0.3, 3.5, 0.3),
+            TreeSpecies::Oak => Vec3::new(0.5, 3.0, 0.5),
+            TreeSpecies::Birch => Vec3::new(0.25, 2.8, 0.25),
+        }
+    }
+
+    fn color(&self) -> Srgba {
+        match self {
+            TreeSpecies::Pine => palettes::css::DARK_GREEN,
+            TreeSpecies::Oak => palettes::basic::GREEN,
+            TreeSpecies::Birch => palettes::css::YELLOW_GREEN,
+        }
+    }
+
+    fn resource_node(&self) -> ResourceNode {
+        ResourceNode {
+            yield_per_hit: vec![(ItemId::Wood, self.wood_per_hit())],
+            yield_on_depleted: Vec::new(),
+            damage_per_hit: 3,
+            required_tool: Some(ToolKind::Axe),
+            effective_tool: Some(ToolKind::Axe),
+            depletion: Depletion::Fell,
+        }
+    }
+}
+
 fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -44,69 +133,184 @@ fn spawn_start_trees(
     for i in 0..nb_trees {
         let step = (i as f32 / nb_trees as f32) * PI * 2.0;
         let dist = rand::random::<f32>() * 10.0 + 2.0;
+        let species = TreeSpecies::ALL[rand::random::<usize>() % TreeSpecies::ALL.len()];
         let x = f32::cos(step) * dist;
-        let y = 1.5;
+        let y = species.size().y / 2.0;
         let z = f32::sin(step) * dist;
         let location = Vec3 { x, y, z };
 
-        spawn_tree(commands, location, meshes, materials);
+        spawn_tree(commands, species, location, meshes, materials);
     }
 }
 
-fn spawn_tree(
+pub fn spawn_tree(
     commands: &mut Commands,
+    species: TreeSpecies,
     loaction: Vec3,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
 ) {
+    let size = species.size();
+
     commands
         .spawn(PbrBundle {
-            mesh: meshes.add(Cuboid::from_size(Vec3 {
-                x: 0.3,
-                y: 3.0,
-                z: 0.3,
-            })),
-            material: materials.add(StandardMaterial::from_color(palettes::basic::GREEN)),
+            mesh: meshes.add(Cuboid::from_size(size)),
+            material: materials.add(StandardMaterial::from_color(species.color())),
             transform: Transform::from_translation(loaction),
             ..Default::default()
         })
-        .insert((RigidBody::Fixed, Collider::capsule_y(1.5, 0.15)))
-        .insert(Health::new_full(10))
-        .insert(Tree)
-        .insert(Interactable);
+        .insert((
+            RigidBody::Fixed,
+            Collider::capsule_y(size.y / 2.0, size.x / 2.0),
+        ))
+        .insert(Health::new_full(species.health()))
+        .insert(species.resource_node())
+        .insert((Tree, species))
+        .insert(Interactable::new(InteractionKind::Harvest, "Chop").with_duration(0.6));
+}
+
+fn direction_from_actor(actor_transform: &Transform, tree_transform: &Transform) -> Vec3 {
+    let direction = tree_transform.translation - actor_transform.translation;
+    Vec3::new(direction.x, 0.0, direction.z).normalize_or(Vec3::X)
+}
+
+fn shake_hit_trees(
+    mut commands: Commands,
+    trees: Query<&Transform, With<Tree>>,
+    actors: Query<&Transform>,
+    mut hit_events: EventReader<NodeHitEvent>,
+) {
+    for hit_event in hit_events.read() {
+        let (Ok(tree_transform), Ok(actor_transform)) =
+            (trees.get(hit_event.node), actors.get(hit_event.actor))
+        else {
+            continue;
+        };
+
+        let direction = direction_from_actor(actor_transform, tree_transform);
+
+        commands.entity(hit_event.node).insert(TreeShake {
+            axis: Vec3::Y.cross(direction),
+            timer: Timer::from_seconds(SHAKE_DURATION, TimerMode::Once),
+        });
+    }
 }
 
-fn handle_tree_interaction(
-    mut trees: Query<&mut Health, With<Tree>>,
-    mut interaction_events: EventReader<InteractionEvent>,
-    mut inventory: ResMut<Inventory>,
+fn update_tree_shake(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut trees: Query<(Entity, &mut Transform, &mut TreeShake)>,
 ) {
-    for interacion_event in interaction_events.read() {
-        // Need more robust entity check -> not always entity_2
-        if let Ok(mut tree_health) = trees.get_mut(interacion_event.entity_2) {
-            tree_health.change(-3);
-
-            if tree_health.is_dead() {
-                inventory.wood += 10;
-            }
+    for (tree_entity, mut tree_transform, mut tree_shake) in &mut trees {
+        tree_shake.timer.tick(time.delta());
+
+        if tree_shake.timer.finished() {
+            tree_transform.rotation = Quat::IDENTITY;
+            commands.entity(tree_entity).remove::<TreeShake>();
+            continue;
         }
+
+        let elapsed = tree_shake.timer.elapsed_secs();
+        let damping = 1.0 - tree_shake.timer.fraction();
+        let angle = f32::sin(elapsed * 40.0) * 0.08 * damping;
+        tree_transform.rotation = Quat::from_axis_angle(tree_shake.axis, angle);
     }
 }
 
-fn despawn_dead_trees(
+fn start_felling(
     mut commands: Commands,
-    trees: Query<
-        (Entity, &Handle<Mesh>, &Handle<StandardMaterial>, &Health),
-        (With<Tree>, Changed<Health>),
-    >,
+    trees: Query<(&Transform, &TreeSpecies), With<Tree>>,
+    actors: Query<(&Transform, Option<&Facing>)>,
+    mut depleted_events: EventReader<NodeDepletedEvent>,
+) {
+    for depleted_event in depleted_events.read() {
+        let (Ok((tree_transform, species)), Ok((actor_transform, facing))) = (
+            trees.get(depleted_event.node),
+            actors.get(depleted_event.actor),
+        ) else {
+            continue;
+        };
+
+        let direction = match facing {
+            Some(facing) => Vec3::new(facing.0.x, 0.0, facing.0.z).normalize_or(Vec3::X),
+            None => direction_from_actor(actor_transform, tree_transform),
+        };
+
+        commands
+            .entity(depleted_event.node)
+            .remove::<(TreeShake, Interactable, ResourceNode)>()
+            .insert((
+                RigidBody::Dynamic,
+                ColliderMassProperties::Mass(TREE_MASS),
+                ExternalForce {
+                    force: Vec3::NEG_Y * FALL_GRAVITY * TREE_MASS,
+                    torque: Vec3::ZERO,
+                },
+                ExternalImpulse {
+                    impulse: Vec3::ZERO,
+                    torque_impulse: Vec3::Y.cross(direction) * FELL_TORQUE_IMPULSE,
+                },
+                Damping {
+                    linear_damping: 0.5,
+                    angular_damping: 1.0,
+                },
+                Sleeping::default(),
+            ))
+            .insert(FallingTree {
+                half_height: species.size().y / 2.0,
+                timeout: Timer::from_seconds(MAX_FALL_TIME, TimerMode::Once),
+            });
+    }
+}
+
+// Wood drops along the trunk wherever the tree came to rest
+fn update_falling_trees(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut trees: Query<(
+        Entity,
+        &Transform,
+        &mut FallingTree,
+        &Sleeping,
+        &TreeSpecies,
+        &Handle<Mesh>,
+        &Handle<StandardMaterial>,
+    )>,
+    mut spawn_pickup_event: EventWriter<SpawnPickupEvent>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
-    for (tree_entity, mesh_handle, material_handle, health) in &trees {
-        if health.is_dead() {
-            commands.entity(tree_entity).despawn_recursive();
-            meshes.remove(mesh_handle);
-            materials.remove(material_handle);
+    for (
+        tree_entity,
+        tree_transform,
+        mut falling_tree,
+        sleeping,
+        species,
+        mesh_handle,
+        material_handle,
+    ) in &mut trees
+    {
+        falling_tree.timeout.tick(time.delta());
+        if !sleeping.sleeping && !falling_tree.timeout.finished() {
+            continue;
+        }
+
+        let trunk = tree_transform.rotation * Vec3::Y * falling_tree.half_height;
+        let base = tree_transform.translation - trunk;
+
+        let wood = species.wood_when_felled();
+        for i in 0..WOOD_PICKUPS_PER_TREE {
+            let along = (i + 1) as f32 / (WOOD_PICKUPS_PER_TREE + 1) as f32 * 2.0;
+            let count = wood / WOOD_PICKUPS_PER_TREE + u32::from(i < wood % WOOD_PICKUPS_PER_TREE);
+
+            spawn_pickup_event.send(SpawnPickupEvent {
+                stack: ItemStack::new(ItemId::Wood, count),
+                position: (base + trunk * along).with_y(0.25),
+            });
         }
+
+        commands.entity(tree_entity).despawn_recursive();
+        meshes.remove(mesh_handle);
+        materials.remove(material_handle);
     }
 }